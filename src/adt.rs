@@ -6,6 +6,8 @@ use std::path::PathBuf;
 use std::fs::File;
 use std::io::Write;
 use crate::translatable::RTranslatable;
+use crate::help_data::HelpData;
+use crate::source_map::SourceMap;
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct Adt(pub Vec<Lang>);
@@ -32,23 +34,18 @@ impl Adt {
     }
 
     pub fn to_r(&self, cont: &Context) -> String {
+        join_chunks(&self.to_r_chunks(cont))
+    }
+
+    /// R code of each statement with the location it comes from
+    pub fn to_r_chunks(&self, cont: &Context) -> Vec<(String, HelpData)> {
         //let mut current_cont = cont.update_classes();
         let current_cont = cont.clone();
-        let mut results = Vec::new();
-        
-        for exp in self.iter() {
-            let (exp_str, _new_cont) = exp.to_r(&current_cont);
-            results.push(exp_str);
-        }
-        let res = results.join("\n");
-        if res == "" {
-            res
-        } else {
-            results.join("\n") + "\n"
-        }
+        self.iter()
+            .map(|exp| (exp.to_r(&current_cont).0, exp.get_help_data()))
+            .collect()
     }
 
-
     pub fn add(self, adt: Adt) -> Adt {
         Adt(self.0.iter().chain(adt.0.iter()).cloned().collect::<Vec<_>>())
     }
@@ -61,8 +58,22 @@ impl Adt {
 
         let app_path = output_dir.join(file_name);
         let mut app = File::create(app_path).unwrap();
-        let content = format!("source('std.R', echo = FALSE)\n\n# Existing types\n{}\n\n{}{}", cont.get_type_converters(), cont.get_adt().to_r(cont), self.to_r(cont));
+        let (header, chunks) = self.to_r_program(cont);
+        let source_map = SourceMap::new(file_name, header.matches('\n').count() + 1, &chunks);
+        let content = format!("{}{}", header, join_chunks(&chunks));
         app.write_all(content.as_bytes()).unwrap();
+        source_map.write(&output_dir.join(format!("{}.map", file_name)));
+    }
+
+    /// Header (types, module environments, generics) and statements of the R file
+    pub fn to_r_program(&self, cont: &Context) -> (String, Vec<(String, HelpData)>) {
+        let header = format!("source('std.R', echo = FALSE)\n\n# Existing types\n{}\n\n{}", cont.get_type_converters(), cont.get_adt().to_r(cont));
+        // the environments of the modules are created by the header, before the generics living in them
+        let body = self.iter()
+            .filter(|lang| !matches!(lang, Lang::ModuleDecl(..)))
+            .cloned()
+            .collect::<Vec<_>>();
+        (header, Adt(body).to_r_chunks(cont))
     }
 
     pub fn push(self, lang: Lang) -> Self {
@@ -72,6 +83,18 @@ impl Adt {
 
 }
 
+fn join_chunks(chunks: &[(String, HelpData)]) -> String {
+    let res = chunks.iter()
+        .map(|(code, _)| code.clone())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if res == "" {
+        res
+    } else {
+        res + "\n"
+    }
+}
+
 impl Default for Adt {
     fn default() -> Adt {
         Adt(vec![])
@@ -3,6 +3,7 @@ use crate::Lang;
 use crate::Context;
 use crate::typing;
 use crate::help_data::HelpData;
+use crate::help_message::ErrorMsg;
 
 #[derive(Debug)]
 pub struct AdtManager {
@@ -30,9 +31,12 @@ impl AdtManager {
         let new_adt = adt.iter()
             .flat_map(|x| if let Lang::Let(_ , _, body, _) = x {Some((x.clone(), body))} else {None})
             .collect::<Vec<_>>();
+        let signatures = adt.iter()
+            .filter(|x| matches!(x, Lang::Signature(..)));
         let header = new_adt.iter()
             .filter(|(_, body)| body.is_undefined())
             .map(|(x, _)| x)
+            .chain(signatures)
             .cloned().collect::<Vec<_>>();
         let body = new_adt.iter()
             .filter(|(_, body)| !body.is_undefined())
@@ -64,6 +68,8 @@ impl AdtManager {
         let context = typing(&base_context, &Lang::Sequence(self.get_header().0.clone(), HelpData::default())).1;
         let (typ, new_context) = typing(&context, &Lang::Sequence(self.get_body().0.clone(), HelpData::default()));
         println!("Type checking:\n{}\n", typ.pretty());
+        new_context.get_warnings().into_iter()
+            .for_each(|warning| eprintln!("{}", warning.display()));
         new_context
     }
 
@@ -3,8 +3,9 @@ use serde::Serialize;
 use crate::Type;
 use crate::kind::Kind;
 
+// the bound is the interface a generic must implement (fn<T: Addable>)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Hash)]
-pub struct ArgumentKind(pub Type, pub Kind);
+pub struct ArgumentKind(pub Type, pub Kind, pub Option<Type>);
 
 impl ArgumentKind {
     pub fn get_kind(&self) -> Kind {
@@ -13,6 +14,19 @@ impl ArgumentKind {
     pub fn get_argument(&self) -> Type {
         self.0.clone()
     }
+    pub fn get_bound(&self) -> Option<Type> {
+        self.2.clone()
+    }
+    pub fn set_bound(self, bound: Option<Type>) -> Self {
+        ArgumentKind(self.0, self.1, bound)
+    }
+    // a recomputed kind keeps the bound declared for the same generic
+    pub fn with_bound_from(self, kinds: &[ArgumentKind]) -> Self {
+        let bound = kinds.iter()
+            .find(|kind| kind.0 == self.0)
+            .and_then(ArgumentKind::get_bound);
+        self.set_bound(bound)
+    }
 }
 
 impl fmt::Display for ArgumentKind {
@@ -23,6 +37,6 @@ impl fmt::Display for ArgumentKind {
 
 impl From<(Type, Kind)> for  ArgumentKind {
    fn from(val: (Type, Kind)) -> Self {
-        ArgumentKind(val.0, val.1)
+        ArgumentKind(val.0, val.1, None)
    } 
 }
@@ -0,0 +1,84 @@
+use crate::Type;
+use crate::Context;
+use crate::TypeError;
+use crate::tint::Tint;
+use crate::tchar::Tchar;
+use crate::help_message::ErrorMsg;
+use crate::index_solver::simplify;
+use crate::unification::type_substitution;
+use crate::type_checker::get_gen_type;
+
+// [#N, num] if #N > 0 or $L if $L in ("a", "b"): the conditions of a
+// dependent type are evaluated once an argument binds its generics.
+
+pub fn conditions(typ: &Type) -> Vec<Type> {
+    match typ {
+        Type::If(_, conditions, _) => conditions.clone(),
+        _ => vec![]
+    }
+}
+
+fn same_value(typ1: &Type, typ2: &Type) -> Option<bool> {
+    match (typ1, typ2) {
+        (Type::Integer(Tint::Val(i), _), Type::Integer(Tint::Val(j), _)) => Some(i == j),
+        (Type::Char(Tchar::Val(a), _), Type::Char(Tchar::Val(b), _)) => Some(a == b),
+        _ => None
+    }
+}
+
+fn compare(ope: &str, i: i32, j: i32) -> bool {
+    match ope {
+        "<" => i < j,
+        "<=" => i <= j,
+        ">" => i > j,
+        ">=" => i >= j,
+        "==" => i == j,
+        _ => i != j
+    }
+}
+
+// None while the generics of the condition aren't known
+fn evaluate(condition: &Type, bindings: &[(Type, Type)]) -> Option<bool> {
+    let substitutions = bindings.iter()
+        .map(|(value, generic)| (generic.clone(), value.clone()))
+        .collect::<Vec<_>>();
+    match condition {
+        Type::Condition(lhs, ope, rhs, _) => {
+            let lhs = simplify(&type_substitution(lhs, &substitutions));
+            let rhs = simplify(&type_substitution(rhs, &substitutions));
+            match (&**ope, &lhs, &rhs) {
+                (Type::In(_), _, Type::Tuple(values, _)) => {
+                    let found = values.iter()
+                        .map(|value| same_value(&lhs, value))
+                        .collect::<Vec<_>>();
+                    if found.contains(&Some(true)) { Some(true) }
+                    else if found.contains(&None) { None }
+                    else { Some(false) }
+                },
+                (Type::Comparison(ope, _), Type::Integer(Tint::Val(i), _), Type::Integer(Tint::Val(j), _))
+                    => Some(compare(ope, *i, *j)),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+/// A type satisfies the conditions of a dependent type unless a condition is false for it
+pub fn satisfies(context: &Context, typ: &Type, expected: &Type) -> bool {
+    match expected {
+        Type::If(base, conditions, _) => {
+            let bindings = get_gen_type(&typ.reduce(context), &base.reduce(context))
+                .unwrap_or_default();
+            conditions.iter().all(|condition| evaluate(condition, &bindings) != Some(false))
+        },
+        _ => true
+    }
+}
+
+/// The bindings of an argument mustn't break the conditions of its parameter
+pub fn check_conditions(conditions: &[Type], bindings: &[(Type, Type)], received: &Type) {
+    if let Some(condition) = conditions.iter().find(|condition| evaluate(condition, bindings) == Some(false)) {
+        panic!("{}", TypeError::UnsatisfiedCondition(condition.clone(), received.clone()).display())
+    }
+}
@@ -39,7 +39,7 @@ impl Default for Config {
         Config {
             compile_mode: CompileMode::Body,
             environment: Environment::StandAlone,
-            immutability: false
+            immutability: true
         }
     }
 }
@@ -9,8 +9,8 @@ use crate::type_comparison;
 use crate::Environment;
 use crate::type_comparison::is_matching;
 use crate::help_data::HelpData;
-use crate::typing;
 use crate::type_checker::match_types;
+use crate::type_checker::typing_with_expected;
 use crate::graph::Graph;
 use crate::type_comparison::reduce_type;
 use crate::TypeError;
@@ -24,6 +24,10 @@ use crate::builder;
 use std::collections::HashSet;
 use crate::unification_map::UnificationMap;
 use crate::function_type::FunctionType;
+use crate::parameters::Parameters;
+use crate::help_message::TypeWarning;
+use std::rc::Rc;
+use std::cell::RefCell;
 
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,6 +37,9 @@ pub struct Context {
    header: Header,
    config: Config,
    kinds: Vec<(Type, Kind)>,
+   parameters: Vec<(String, Parameters)>,
+   implementations: Vec<(Type, Type)>, // (interface, type) of the impl blocks
+   warnings: Rc<RefCell<Vec<TypeWarning>>>, // shared by all the contexts of a type checking
 }
 
 impl Default for Context {
@@ -42,7 +49,10 @@ impl Default for Context {
             config: Config::default(),
             typing_context: VarType::new(),
             kinds: vec![],
+            parameters: vec![],
+            implementations: vec![],
             subtypes: Graph::new(),
+            warnings: Rc::new(RefCell::new(vec![])),
         }
     }
 }
@@ -62,6 +72,21 @@ impl From<Vec<(Lang, Type)>> for  Context {
 
 //main
 impl Context {
+    /// Context without any subtype hierarchy, used by the Graph itself
+    /// (Context::default builds a Graph which needs a Context to compare types)
+    pub fn empty() -> Context {
+        Context { 
+            header: Header::default(),
+            config: Config::default(),
+            typing_context: VarType::new(),
+            kinds: vec![],
+            parameters: vec![],
+            implementations: vec![],
+            subtypes: Graph::root_only(),
+            warnings: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
     pub fn new(types: Vec<(Var, Type)>, kinds: Vec<(Type, Kind)>) -> Context {
         Context {
             typing_context: types.into(),
@@ -76,31 +101,32 @@ impl Context {
 
     pub fn get_type_from_variable(&self, var: &Var) -> Option<Type> {
         self.variables().flat_map(|(var2, type_)| {
-            let Var(name1, path1, perm1, bo1, typ1, _h1) = var;
-            let Var(name2, path2, perm2, bo2, typ2, _h2) = var2;
+            let Var(name1, path1, perm1, muta1, opa1, typ1, _h1) = var;
+            let Var(name2, path2, perm2, muta2, opa2, typ2, _h2) = var2;
             let conditions = (name1 == name2) &&
                 (path1 == path2) && (perm1 == perm2) &&
-                (bo1 == bo2) && (type_comparison::is_matching(self, typ1, typ2));
+                (muta1 == muta2) && (opa1 == opa2) && (type_comparison::is_matching(self, typ1, typ2));
             if conditions { Some(type_.clone()) } else { None }
         }).next()
     }
 
     pub fn get_type_from_aliases(&self, var: &Var) -> Option<Type> {
         self.aliases().flat_map(|(var2, type_)| {
-            let Var(name1, path1, perm1, bo1, typ1, _h1) = var;
-            let Var(name2, path2, perm2, bo2, typ2, _h2) = var2;
+            let Var(name1, path1, perm1, muta1, opa1, typ1, _h1) = var;
+            let Var(name2, path2, perm2, muta2, opa2, typ2, _h2) = var2;
             let conditions = (name1 == name2) &&
                 (path1 == path2) && (perm1 == perm2) &&
-                (bo1 == bo2) && (type_comparison::is_matching(self, typ1, typ2));
+                (muta1 == muta2) && (opa1 == opa2) && (type_comparison::is_matching(self, typ1, typ2));
             if conditions { Some(type_.clone()) } else { None }
         }).next()
     }
 
+    // a type alias doesn't carry the visibility of its declaration
     fn is_matching(&self, var1: &Var, var2: &Var) -> bool {
-        let Var(name1, path1, perm1, _bo1, params1, _h1) = var1;
-        let Var(name2, path2, perm2, _bo2, params2, _h2) = var2;
+        let Var(name1, path1, _perm1, _muta1, _opa1, params1, _h1) = var1;
+        let Var(name2, path2, _perm2, _muta2, _opa2, params2, _h2) = var2;
         (name1 == name2) &&
-            (path1 == path2) && (perm1 == perm2) &&
+            (path1 == path2) &&
             (type_comparison::is_matching(self, params1, params2))
     }
 
@@ -130,8 +156,13 @@ impl Context {
         let var_type = self.typing_context.clone()
             .push_var_type(&[(lang, typ.clone())])
             .push_types(&types);
+        // the hierarchy compares types without aliases
+        let reduced_types = types.iter()
+            .map(|t| t.reduce(context))
+            .filter(|t| !t.keeps_alias())
+            .collect::<Vec<_>>();
         let mut typ_hie = self.subtypes.clone();
-        typ_hie.update(&types);
+        typ_hie.update(&reduced_types);
         Context {
             typing_context: var_type, 
             subtypes: typ_hie,
@@ -139,6 +170,22 @@ impl Context {
         }
     }
 
+    /// The aliases of a sequence are known before their definition: a type
+    /// can refer to itself or to a type defined after it
+    pub fn declare_aliases(self, exprs: &[Lang]) -> Self {
+        let aliases = exprs.iter()
+            .filter_map(|expr| match expr {
+                Lang::Alias(name, params, typ, h) => Some((
+                    name.clone().set_type(Type::Params(params.to_vec(), h.clone()), &self),
+                    typ.clone())),
+                _ => None
+            }).collect::<Vec<_>>();
+        Self {
+            typing_context: self.typing_context.clone().push_var_type(&aliases),
+            ..self
+        }
+    }
+
     pub fn push_types(self, types: &[Type]) -> Self {
         Self {
             typing_context: self.typing_context.push_types(types),
@@ -152,10 +199,25 @@ impl Context {
            header: self.header.clone(),
            config: self.config.clone(),
            kinds: self.kinds.clone(),
-           typing_context: self.typing_context.clone()
+           parameters: self.parameters.clone(),
+           implementations: self.implementations.clone(),
+           typing_context: self.typing_context.clone(),
+           warnings: self.warnings.clone()
+        }
+    }
+
+    /// A warning is kept once, even when its expression is typed again
+    pub fn warn(&self, warning: TypeWarning) {
+        let mut warnings = self.warnings.borrow_mut();
+        if !warnings.contains(&warning) {
+            warnings.push(warning)
         }
     }
 
+    pub fn get_warnings(&self) -> Vec<TypeWarning> {
+        self.warnings.borrow().clone()
+    }
+
     pub fn get_type_from_existing_variable(&self, var: Var) -> Type {
         if let Type::RFunction(_) = var.get_type() {
             var.get_type()
@@ -163,7 +225,7 @@ impl Context {
             self.typing_context.variables()
                .find(|(v, _)| var.match_with(v, self))
                .map(|(_, ty)| ty)
-               .expect(&TypeError::UndefinedVariable(var.to_language()).display())
+               .unwrap_or_else(|| panic!("{}", TypeError::UndefinedVariable(var.to_language()).display()))
                .clone()
         }
     }
@@ -198,7 +260,9 @@ impl Context {
     }
 
     pub fn get_classes(&self, t: &Type) -> Option<String> {
-        let res = self.subtypes.get_supertypes(t)
+        // a recursive or opaque type is out of the hierarchy
+        let supertypes = if t.keeps_alias() { vec![] } else { self.subtypes.get_supertypes(t) };
+        let res = supertypes
             .iter().map(|typ| self.get_class(typ))
             .collect::<HashSet<_>>()
             .iter().cloned().collect::<Vec<_>>()
@@ -214,12 +278,26 @@ impl Context {
         self.typing_context.variables()
             .filter(|(var, _typ)| {
                 let related_typ = var.get_type();
+                // a function written for an interface doesn't implement it
                 related_typ != Type::Empty(HelpData::default())
+                    && !matches!(related_typ.reduce(self), Type::Interface(_, _))
                     && is_matching(self, t, &related_typ) 
             }).cloned()
             .collect()
     }
 
+    /// The distinct signatures declared under the name and path of var, the latest first
+    pub fn get_signatures(&self, var: &Var) -> Vec<Type> {
+        self.typing_context.variables()
+            .filter(|(var2, typ)| var2.get_name() == var.get_name()
+                    && var2.get_path() == var.get_path()
+                    && matches!(typ, Type::Function(_, _, _, _)))
+            .fold(vec![], |mut signatures, (_, typ)| {
+                if !signatures.contains(typ) { signatures.push(typ.clone()) }
+                signatures
+            })
+    }
+
     pub fn get_embeddings(&self, t: &Type) -> (Vec<(Var, Type)>, Context) {
         match t {
             Type::Record(arg_typs, _) => {
@@ -275,7 +353,7 @@ impl Context {
                        var.clone(),
                         Type::Empty(HelpData::default()),
                         Box::new(
-                           Lang::Function(kinds.to_vec(), new_args, new_t2,
+                           Lang::Function(kinds.to_vec(), new_args, vec![], new_t2,
                                           Box::new(build_concret_function(&manips, manip1, var.clone())), h.clone())
                                 ),
                             h.clone())
@@ -337,6 +415,25 @@ impl Context {
         format!("{}{}", msg, self.display_typing_context())
     }
 
+    pub fn push_parameters(self, fn_name: &str, params: Parameters) -> Self {
+        if params.is_empty() {
+            self
+        } else {
+            Context {
+                parameters: self.parameters.iter().cloned()
+                    .chain([(fn_name.to_string(), params)])
+                    .collect(),
+                ..self
+            }
+        }
+    }
+
+    pub fn get_parameters(&self, fn_name: &str) -> Option<Parameters> {
+        self.parameters.iter().rev()
+            .find(|(name, _)| name == fn_name)
+            .map(|(_, params)| params.clone())
+    }
+
     pub fn push_alias(self, alias_name: String, typ: Type) -> Self {
         Context {
             typing_context: self.typing_context.push_alias(alias_name, typ),
@@ -374,14 +471,27 @@ impl Context {
         self.config.immutability 
     }
 
-    pub fn get_unification_map(&self, values: &[Lang], param_types: &[Type]) 
+    /// arg_types are the types of the values, already computed by the caller
+    pub fn get_unification_map(&self, values: &[Lang], arg_types: &[Type], param_types: &[Type]) 
         -> Option<UnificationMap> {
-        let res = values.iter()
-            .map(|val| typing(self, val).0)
+        // lambdas come last: their parameter types are inferred from the other arguments
+        let (lambdas, others): (Vec<_>, Vec<_>) = values.iter()
+            .zip(arg_types.iter())
             .zip(param_types.iter())
-            .flat_map(|(val_typ, par_typ)| match_types(self, &val_typ, par_typ))
+            .partition(|((val, _), _)| matches!(val, Lang::Lambda(..)));
+        let res = others.iter()
+            .flat_map(|((_, val_typ), par_typ)| match_types(self, val_typ, par_typ))
             .flatten()
             .collect::<Vec<_>>();
+        let partial_map = UnificationMap::new(res.clone());
+        let res2 = lambdas.iter()
+            .map(|((val, _), par_typ)| {
+                let expected = partial_map.apply_unification_type(self, par_typ).0;
+                (typing_with_expected(self, val, &expected).0, par_typ)
+            })
+            .flat_map(|(val_typ, par_typ)| match_types(self, &val_typ, par_typ))
+            .flatten();
+        let res = res.into_iter().chain(res2).collect::<Vec<_>>();
         (res.len() > 0).then(|| UnificationMap::new(res))
     }
 
@@ -394,10 +504,15 @@ impl Context {
                                    var.get_name(),
                                    v.iter().cloned()
                                    .collect::<Vec<_>>().join(", ")),
+                    // the values of an opaque type keep its class
+                    _ if var.is_opaque() => format!("{} <- function(x) x |> struct(c({}, {}))",
+                           var.get_name(),
+                           self.get_class(&var.clone().to_alias()),
+                           self.get_class(typ)),
                     _ => format!("{} <- function(x) x |> struct(c({}, {}))",
                            var.get_name(),
                            self.get_class(typ),
-                           self.get_classes(typ).unwrap()) 
+                           self.get_classes(&typ.reduce(self)).unwrap()) 
                 }
             }).collect::<Vec<_>>().join("\n")
     }
@@ -420,6 +535,30 @@ impl Context {
         }
     }
 
+    /// The type of an earlier implementation of the interface that overlaps typ
+    pub fn get_implementation(&self, interface: &Type, typ: &Type) -> Option<Type> {
+        self.implementations.iter()
+            .find(|(interface2, typ2)| interface2 == interface
+                  && (is_matching(self, typ, typ2) || is_matching(self, typ2, typ)))
+            .map(|(_, typ2)| typ2.clone())
+    }
+
+    pub fn push_implementation(self, interface: &Type, typ: &Type) -> Self {
+        Self {
+            implementations: self.implementations.iter().cloned()
+                .chain([(interface.clone(), typ.clone())])
+                .collect(),
+            ..self
+        }
+    }
+
+    pub fn open_module(&self, path: &str) -> Self {
+        Self {
+            typing_context: self.typing_context.clone().open_module(path),
+            ..self.clone()
+        }
+    }
+
 }
 
 fn build_concret_function(m: &[Manip], end: Manip, name: Var) -> Lang {
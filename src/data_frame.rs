@@ -0,0 +1,72 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::TypeError;
+use crate::help_message::ErrorMsg;
+use crate::help_data::HelpData;
+use crate::argument_type::ArgumentType;
+use crate::type_checker::typing;
+
+// A DataFrame<{id: int, name: char}> is the record of its column types with
+// a number of rows. Its columns are arrays of that length.
+
+fn record_of_columns(context: &Context, value: &Lang, h: &HelpData) -> (Type, Type) {
+    let typ = typing(context, value).0.reduce(context);
+    let columns = match &typ {
+        Type::Record(fields, _) if !fields.is_empty() => fields.iter()
+            .map(|arg_typ| match arg_typ.get_type().reduce(context) {
+                Type::Array(len, elem_ty, _) => Some((arg_typ.get_argument(), *len, *elem_ty)),
+                _ => None
+            })
+            .collect::<Option<Vec<_>>>(),
+        _ => None
+    }.unwrap_or_else(|| panic!("{}", TypeError::WrongColumns(typ.clone(), typ.get_help_data()).display()));
+    let rows = columns[0].1.clone();
+    let same_length = columns.iter()
+        .all(|(_, len, _)| matches!((len, &rows), (Type::Integer(i, _), Type::Integer(j, _)) if i == j));
+    if !same_length {
+        panic!("{}", TypeError::WrongColumns(typ.clone(), typ.get_help_data()).display())
+    }
+    let schema = columns.into_iter()
+        .map(|(name, _, elem_ty)| ArgumentType(name, elem_ty.generalize(), false))
+        .collect::<Vec<_>>();
+    (rows, Type::Record(schema, h.clone()))
+}
+
+// rbind(df1, df2): the columns must be the same
+fn row_binding(context: &Context, df1: Type, df2: Type, h: &HelpData) -> Type {
+    match (df1.reduce(context), df2.reduce(context)) {
+        (Type::DataFrame(rows1, schema1, _), Type::DataFrame(rows2, schema2, _))
+            if schema1.is_subtype(&schema2, context) && schema2.is_subtype(&schema1, context) => {
+            let rows = Type::Add(rows1, rows2, h.clone()).index_calculation();
+            Type::DataFrame(Box::new(rows), schema1, h.clone())
+        },
+        _ => panic!("{}", TypeError::Param(df1, df2).display())
+    }
+}
+
+/// Type of the data frame functions (data.frame, rbind), None for the other calls
+pub fn data_frame_call(context: &Context, fn_name: &str, values: &[Lang], h: &HelpData) -> Option<Type> {
+    match (fn_name, values) {
+        ("data__frame", [value]) => {
+            let (rows, schema) = record_of_columns(context, value, h);
+            Some(Type::DataFrame(Box::new(rows), Box::new(schema), h.clone()))
+        },
+        ("rbind", [value1, value2]) => {
+            let df1 = typing(context, value1).0;
+            matches!(df1.reduce(context), Type::DataFrame(..))
+                .then(|| row_binding(context, df1, typing(context, value2).0, h))
+        },
+        _ => None
+    }
+}
+
+/// df$score is an array with one value for each row
+pub fn column_type(rows: &Type, schema: &Type, name: &str) -> Option<Type> {
+    match schema {
+        Type::Record(fields, h) => fields.iter()
+            .find(|arg_typ| arg_typ.get_argument_str() == name)
+            .map(|arg_typ| Type::Array(Box::new(rows.clone()), Box::new(arg_typ.get_type()), h.clone())),
+        _ => None
+    }
+}
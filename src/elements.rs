@@ -22,6 +22,7 @@ use nom::character::complete::digit1;
 use std::collections::HashSet;
 use crate::types::label;
 use crate::types::if_type;
+use crate::types::generic_bounds;
 use nom::sequence::terminated;
 use nom::branch::alt;
 use nom::sequence::delimited;
@@ -32,6 +33,8 @@ use nom::Parser;
 use nom_locate::LocatedSpan;
 use crate::help_data::HelpData;
 use nom::combinator::recognize;
+use nom::combinator::not;
+use nom::combinator::peek;
 use crate::help_message::SyntaxError;
 use crate::help_message::ErrorMsg;
 use nom::bytes::complete::take_while1;
@@ -86,6 +89,15 @@ fn boolean(s: Span) -> IResult<Span,Lang> {
     }
 }
 
+// the missing value, typed Empty
+fn na(s: Span) -> IResult<Span, Lang> {
+    let res = terminated(terminated(tag("NA"), not(alphanumeric1)), multispace0).parse(s);
+    match res {
+        Ok((s, na)) => Ok((s, Lang::Na(na.into()))),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn chars(s: Span) -> IResult<Span, Lang> {
     let res = terminated(alt((
             (tag("\""), many0(none_of("\"")), tag("\"")),
@@ -138,7 +150,7 @@ fn module_path(s: Span) -> IResult<Span, (String, HelpData)> {
             let res = v.iter()
                 .map(|(name, _)| name.clone())
                 .collect::<Vec<_>>()
-                .join("/");
+                .join("::");
             Ok((s, (res, v[0].1.clone())))
         },
         Err(r) => Err(r)
@@ -174,15 +186,40 @@ pub fn variable(s: Span) -> IResult<Span, Lang> {
 }
 
 
-pub fn argument(s: Span) -> IResult<Span, ArgumentType> {
+// x: int  or  scale: num = 1.0
+pub fn parameter(s: Span) -> IResult<Span, (ArgumentType, Option<ArgumentValue>)> {
     let res = (
         terminated(label, multispace0),
         terminated(tag(":"), multispace0),
-        ltype,
+        alt((if_type, ltype)),
+        opt(preceded(terminated(tag("="), multispace0), single_element)),
+        opt(terminated(tag(","), multispace0))
+                ).parse(s);
+    match res {
+        Ok((s, (e1, _, e2, default, _))) => {
+            let arg_typ = ArgumentType(e1, e2, false);
+            let default = default
+                .map(|val| ArgumentValue(arg_typ.get_argument_str(), val));
+            Ok((s, (arg_typ, default)))
+        },
+        Err(r) => Err(r)
+    }
+}
+
+// x  or  scale = 1.0 (the type is inferred)
+fn untyped_parameter(s: Span) -> IResult<Span, (ArgumentType, Option<ArgumentValue>)> {
+    let res = (
+        terminated(label, multispace0),
+        opt(preceded(terminated(tag("="), multispace0), single_element)),
         opt(terminated(tag(","), multispace0))
                 ).parse(s);
     match res {
-        Ok((s, (e1, _, e2, _))) => Ok((s, ArgumentType(e1, e2, false))),
+        Ok((s, (e1, default, _))) => {
+            let arg_typ = ArgumentType(e1, builder::empty_type(), false);
+            let default = default
+                .map(|val| ArgumentValue(arg_typ.get_argument_str(), val));
+            Ok((s, (arg_typ, default)))
+        },
         Err(r) => Err(r)
     }
 }
@@ -210,7 +247,7 @@ pub fn function_symbol(s: Span) -> IResult<Span, Span> {
     alt((tag("function"), tag("func"), tag("fn"))).parse(s)
 }
 
-fn extract_generics(args: &[ArgumentType], ret_typ: &Type) -> Vec<ArgumentKind> {
+pub fn extract_generics(args: &[ArgumentType], ret_typ: &Type) -> Vec<ArgumentKind> {
     args.iter()
         .map(|at| at.get_type())
         .chain([ret_typ.clone()].iter().cloned())
@@ -221,6 +258,21 @@ fn extract_generics(args: &[ArgumentType], ret_typ: &Type) -> Vec<ArgumentKind>
         .collect::<Vec<_>>()
 }
 
+// gives each generic the bound declared for it in fn<T: Bound>
+fn bind_generics(kinds: Vec<ArgumentKind>, bounds: &[(Type, Option<Type>)]) -> Vec<ArgumentKind> {
+    let declared = bounds.iter()
+        .map(|(gen, bound)| ArgumentKind::from((gen.clone(), gen.get_kind())).set_bound(bound.clone()))
+        .collect::<Vec<_>>();
+    let kinds = kinds.into_iter()
+        .map(|kind| kind.with_bound_from(&declared))
+        .collect::<Vec<_>>();
+    // a declared generic left to inference is kept with its bound
+    let missing = declared.into_iter()
+        .filter(|kind| !kinds.iter().any(|k| k.get_argument() == kind.get_argument()))
+        .collect::<Vec<_>>();
+    kinds.into_iter().chain(missing).collect()
+}
+
 fn parse_block(input: Span) -> IResult<Span, Span> {
     recognize(parse_nested_braces).parse(input)
 }
@@ -236,6 +288,63 @@ fn parse_nested_braces(input: Span) -> IResult<Span, Span> {
     )).parse(input)
 }
 
+fn parse_nested_parens(input: Span) -> IResult<Span, Span> {
+    recognize(delimited(
+        tag("("),
+        many0(alt((
+            parse_nested_parens,
+            recognize(take_while1(|c| c != '(' && c != ')')),
+        ))),
+        tag(")"),
+    )).parse(input)
+}
+
+// names used in a formula, function names (log in log(z)) excepted
+fn formula_variables(formula: &str) -> Vec<String> {
+    let mut variables: Vec<String> = vec![];
+    let mut chars = formula.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphabetic() || c == '.' {
+            let mut name = c.to_string();
+            while let Some(&next) = chars.peek().filter(|n| n.is_alphanumeric() || **n == '_' || **n == '.') {
+                name.push(next);
+                chars.next();
+            }
+            while chars.peek().is_some_and(|n| n.is_whitespace()) {
+                chars.next();
+            }
+            let is_function = chars.peek() == Some(&'(');
+            if !is_function && name != "." && !variables.contains(&name) {
+                variables.push(name)
+            }
+        } else if c.is_numeric() {
+            while chars.peek().is_some_and(|n| n.is_alphanumeric() || *n == '.') {
+                chars.next();
+            }
+        }
+    }
+    variables
+}
+
+// formula(y ~ x + log(z))
+fn formula(s: Span) -> IResult<Span, Lang> {
+    let res = (
+        tag("formula"),
+        parse_nested_parens,
+        multispace0
+        ).parse(s.clone());
+    match res {
+        Ok((s, (start, body, _))) if body.fragment().contains('~') => {
+            let fragment = body.fragment();
+            let text = fragment[1..fragment.len()-1].trim().to_string();
+            let variables = formula_variables(&text);
+            Ok((s, Lang::Formula(text, variables, start.into())))
+        },
+        Ok(_) => Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Tag))),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn r_function(s: Span) -> IResult<Span, Lang> {
     let res = (
         terminated(tag("function"), multispace0),
@@ -259,28 +368,35 @@ pub fn r_function(s: Span) -> IResult<Span, Lang> {
 pub fn simple_function(s: Span) -> IResult<Span, Lang> {
     let res = (
         terminated(tag("fn"), multispace0),
+        opt(generic_bounds),
         terminated(tag("("), multispace0),
-        many0(argument),
+        many0(alt((parameter, untyped_parameter))),
         terminated(tag(")"), multispace0),
         opt(terminated(tag(":"), multispace0)),
         opt(terminated(alt((if_type, ltype)), multispace0)),
         scope
           ).parse(s);
     match res {
-        Ok((s, (_, _, args, _, Some(_), Some(typ), exp))) =>{
-            let gen_vec = extract_generics(&args, &typ);
-            Ok((s, Lang::Function(gen_vec, args, typ, Box::new(exp), HelpData::default())))
+        Ok((s, (_, bounds, _, params, _, Some(_), Some(typ), exp))) =>{
+            let (args, defaults): (Vec<_>, Vec<_>) = params.into_iter().unzip();
+            let defaults = defaults.into_iter().flatten().collect::<Vec<_>>();
+            let gen_vec = bind_generics(extract_generics(&args, &typ), &bounds.unwrap_or_default());
+            Ok((s, Lang::Function(gen_vec, args, defaults, typ, Box::new(exp), HelpData::default())))
         },
-        Ok((_s, (_, _, _args, _cp, None, None, _exp))) 
+        // the return type is inferred from the body
+        Ok((s, (_, bounds, _, params, _cp, None, None, exp))) 
             => {
-                panic!("You forgot to specify the function return type: 'fn(...): Type'");
+            let (args, defaults): (Vec<_>, Vec<_>) = params.into_iter().unzip();
+            let defaults = defaults.into_iter().flatten().collect::<Vec<_>>();
+            let gen_vec = bind_generics(vec![], &bounds.unwrap_or_default());
+            Ok((s, Lang::Function(gen_vec, args, defaults, builder::empty_type(), Box::new(exp), HelpData::default())))
             }, 
-        Ok((_s, (_, _, _args, _, Some(_), None, _exp))) 
+        Ok((_s, (_, _, _, _args, _, Some(_), None, _exp))) 
             => {
             println!("Hey You forgot to specify the function return type after the ':' : 'fn(...): Type'");
             exit(1)
             },
-        Ok((_s, (_, _, _args, _, None, Some(typ), _exp))) 
+        Ok((_s, (_, _, _, _args, _, None, Some(typ), _exp))) 
             => {
                 println!(
                     "The type '{}' should be preceded by a ':' :\n 'fn(...): {}'", 
@@ -303,19 +419,49 @@ fn values(s: Span) -> IResult<Span, Vec<Lang>> {
             terminated(opt(tag(",")), multispace0))).parse(s)
 }
 
+// xs[2], xs[i + 1], xs[2:5], xs[-1], xs[xs > 0]
+// -2 (the generic parser reads it as a subtraction)
+fn negative_index(s: Span) -> IResult<Span, Lang> {
+    let res = (tag("-"), integer).parse(s);
+    match res {
+        Ok((s, (_, Lang::Integer(n, h)))) => Ok((s, Lang::Integer(-n, h))),
+        Ok((s, (_, lang))) => Ok((s, lang)),
+        Err(r) => Err(r)
+    }
+}
+
+// xs[[i + 1]] (the keys of xs[[2]] and l[["name"]] are accessors of a chain)
+fn list_indexing(s: Span) -> IResult<Span, Lang> {
+    let res = (
+            alt((scope, variable)),
+            terminated(tag("[["), multispace0),
+            parse_elements,
+            terminated(tag("]]"), multispace0)
+          ).parse(s.clone());
+    match res {
+        Ok((_, (_, _, Lang::Integer(..) | Lang::Char(..) | Lang::Variable(..), _)))
+            => Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Tag))),
+        Ok((s, (exp, _, index, _))) 
+            => Ok((s, Lang::ListIndexing(Box::new(exp.clone()), Box::new(index), exp.into()))),
+        Err(r) => Err(r)
+    }
+}
+
 fn array_indexing(s: Span) -> IResult<Span, Lang> {
+    if let Ok(res) = list_indexing(s.clone()) {
+        return Ok(res)
+    }
     let res = (
             alt((scope, variable)),
-            terminated(tag("["), multispace0),
-            integer,
+            terminated(terminated(tag("["), not(tag("["))), multispace0),
+            alt((terminated(negative_index, peek(tag("]"))), parse_elements)),
             terminated(tag("]"), multispace0),
             multispace0
           ).parse(s);
     match res {
-        Ok((s, (exp, _, Lang::Integer(n, _), _, _))) 
-            => Ok((s, Lang::ArrayIndexing(Box::new(exp.clone()), n, exp.into()))),
+        Ok((s, (exp, _, index, _, _))) 
+            => Ok((s, Lang::ArrayIndexing(Box::new(exp.clone()), Box::new(index), exp.into()))),
         Err(r) => Err(r),
-        _ => todo!()
     }
 }
 
@@ -578,15 +724,52 @@ fn vectorial_bloc(s: Span) -> IResult<Span, Lang> {
     }
 }
 
-fn lambda(s: Span) -> IResult<Span, Lang> {
-    let res = preceded(tag("~"), element_chain).parse(s);
+// ~ x + 1 (the parameter is always x)
+fn tilde_lambda(s: Span) -> IResult<Span, Lang> {
+    let res = preceded(terminated(tag("~"), multispace0), element_chain).parse(s);
     match res {
         Ok((s, e)) 
-            => Ok((s, Lang::Lambda(Box::new(e.clone()), e.into()))),
+            => Ok((s, Lang::Lambda(
+                        vec![ArgumentType::new("x", &builder::empty_type())],
+                        Box::new(e.clone()), e.into()))),
         Err(r) => Err(r)
     }
 }
 
+// a: int  or  b (the type is inferred)
+fn lambda_parameter(s: Span) -> IResult<Span, ArgumentType> {
+    let res = (
+        terminated(label, multispace0),
+        opt(preceded(terminated(tag(":"), multispace0), ltype)),
+        opt(terminated(tag(","), multispace0))
+                ).parse(s);
+    match res {
+        Ok((s, (e1, typ, _))) 
+            => Ok((s, ArgumentType(e1, typ.unwrap_or(builder::empty_type()), false))),
+        Err(r) => Err(r)
+    }
+}
+
+// \(a: int, b) a + b
+fn typed_lambda(s: Span) -> IResult<Span, Lang> {
+    let res = (
+        tag("\\"),
+        terminated(tag("("), multispace0),
+        many0(lambda_parameter),
+        terminated(tag(")"), multispace0),
+        element_chain
+        ).parse(s);
+    match res {
+        Ok((s, (bs, _, params, _, e))) 
+            => Ok((s, Lang::Lambda(params, Box::new(e), bs.into()))),
+        Err(r) => Err(r)
+    }
+}
+
+fn lambda(s: Span) -> IResult<Span, Lang> {
+    alt((typed_lambda, tilde_lambda)).parse(s)
+}
+
 
 // main
 pub fn single_element(s: Span) -> IResult<Span,Lang> {
@@ -594,6 +777,7 @@ pub fn single_element(s: Span) -> IResult<Span,Lang> {
             range,
             lambda,
             boolean,
+            na,
             number,
             integer,
             chars,
@@ -604,6 +788,7 @@ pub fn single_element(s: Span) -> IResult<Span,Lang> {
             r_function,
             function,
             tuple_exp,
+            formula,
             function_application,
             array_indexing,
             variable,
@@ -649,22 +834,32 @@ fn op_reverse(v: &mut Vec<(Lang, Op)>) -> Lang {
         (p, Op::NotEq(_)) 
 			=> Lang::NotEq(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
         (p, Op::LesserThan(_)) 
-			=> Lang::LesserThan(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
+			=> Lang::LesserThan(Box::new(op_reverse(v)), Box::new(p.clone()), p.into()),
         (p, Op::GreaterThan(_)) 
-			=> Lang::GreaterThan(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
+			=> Lang::GreaterThan(Box::new(op_reverse(v)), Box::new(p.clone()), p.into()),
         (p, Op::LesserOrEqual(_)) 
-			=> Lang::LesserOrEqual(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
+			=> Lang::LesserOrEqual(Box::new(op_reverse(v)), Box::new(p.clone()), p.into()),
         (p, Op::GreaterOrEqual(_)) 
-			=> Lang::GreaterOrEqual(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
+			=> Lang::GreaterOrEqual(Box::new(op_reverse(v)), Box::new(p.clone()), p.into()),
         (p, Op::Modu(_)) 
             => Lang::Modu(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
         (p, Op::Modu2(_)) 
             => Lang::Modu2(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
-        (Lang::FunctionApp(name, params, fn_typ, h1), Op::Pipe(_h2)) 
-            => { // (UFC) add the "object" as te first parameter of the function call
-                let res = [op_reverse(v)].iter().chain(params.iter()).cloned().collect::<Vec<_>>();
-                Lang::FunctionApp(name, res, fn_typ, h1.clone())
+        (Lang::FunctionApp(name, params, fn_typ, _h1), Op::Pipe(h2)) 
+            => { // (UFC) the "object" takes the place of _ or becomes the first parameter
+                // the call is located at its |> so an error points at the stage
+                let object = op_reverse(v);
+                let res = if params.iter().any(Lang::is_placeholder) {
+                    params.iter()
+                        .map(|param| if param.is_placeholder() { object.clone() } else { param.clone() })
+                        .collect::<Vec<_>>()
+                } else {
+                    [object].iter().chain(params.iter()).cloned().collect::<Vec<_>>()
+                };
+                Lang::FunctionApp(name, res, fn_typ, h2)
             }
+        (p @ Lang::Variable(_, _, _, _, _, _), Op::Pipe(h)) // x |> f is f(x)
+            => Lang::FunctionApp(Box::new(p.clone()), vec![op_reverse(v)], builder::empty_type(), h),
         (p, Op::Pipe(_)) => Lang::Chain(Box::new(p.clone()), Box::new(op_reverse(v)), p.into()),
         (p, Op::Pipe2(_)) => {
             let res = match p.clone() {
@@ -895,6 +1090,19 @@ mod tests {
         assert_eq!(res, builder::empty_lang());
     }
 
+    // the operand parsed last is the right one, as for the arithmetic operators
+    #[test]
+    fn test_comparison_operand_order() {
+        let res = element_chain("x < 3".into()).unwrap().1;
+        match res {
+            Lang::LesserThan(left, right, _) => {
+                assert!(matches!(*left, Lang::Variable(..)));
+                assert!(matches!(*right, Lang::Integer(..)));
+            },
+            _ => panic!("not a comparison")
+        }
+    }
+
     #[test]
     fn test_function_no_parameters(){
         let res = function("fn () : Number { 7 }".into()).unwrap().1;
@@ -1236,6 +1444,12 @@ mod tests {
         assert_eq!(res, builder::empty_lang());
     }
 
+    #[test]
+    fn test_formula_variables() {
+        let res = formula_variables("y ~ x + log(z) + I(x^2)");
+        assert_eq!(res, vec!["y", "x", "z"]);
+    }
+
     #[test]
     fn test_array_indexing1() {
         let res = array_indexing("hey[1]".into()).unwrap().1;
@@ -0,0 +1,58 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::TypeError;
+use crate::builder;
+use crate::help_message::ErrorMsg;
+use crate::help_data::HelpData;
+use crate::argument_type::ArgumentType;
+use crate::tchar::Tchar;
+use crate::type_checker::typing;
+
+// Factor<"low" | "mid" | "high"> is a char restricted to its levels.
+// The levels keep their order since R uses it: factor(x, levels = c(...)).
+
+fn factor_levels(typ: &Type) -> Option<Vec<String>> {
+    match typ {
+        Type::Factor(levels, _) => Some(levels.clone()),
+        Type::Array(_, elem_ty, _) => factor_levels(elem_ty),
+        _ => None
+    }
+}
+
+/// Type of the functions on factors (levels, nlevels, table), None for the other calls
+pub fn factor_call(context: &Context, fn_name: &str, values: &[Lang], h: &HelpData) -> Option<Type> {
+    if !["levels", "nlevels", "table"].contains(&fn_name) {
+        return None
+    }
+    let levels = values.first()
+        .and_then(|value| factor_levels(&typing(context, value).0.reduce(context)))?;
+    let nb_levels = builder::integer_type(levels.len() as i32).set_help_data(h.clone());
+    match fn_name {
+        "levels" => Some(Type::Array(Box::new(nb_levels), Box::new(builder::character_type_default()), h.clone())),
+        "nlevels" => Some(nb_levels),
+        // one count for each level
+        _ => Some(Type::Record(levels.iter()
+                .map(|level| ArgumentType::new(level, &builder::integer_type_default()))
+                .collect(), h.clone()))
+    }
+}
+
+/// The char literals given where a factor is expected must be among its levels
+pub fn check_levels(context: &Context, value: &Lang, expected: &Type) {
+    match (value, expected.reduce(context)) {
+        (Lang::Char(level, h), Type::Factor(levels, h2)) if !levels.contains(level)
+            => panic!("{}", TypeError::UnknownLevel(level.clone(), Type::Factor(levels, h2), h.clone()).display()),
+        (Lang::Array(values, _), Type::Array(_, elem_ty, _))
+            => values.iter().for_each(|value| check_levels(context, value, &elem_ty)),
+        _ => ()
+    }
+}
+
+/// A level only goes in the branch of the same literal
+pub fn is_case_of(context: &Context, case: &Type, pattern: &Type) -> bool {
+    match (case, pattern) {
+        (Type::Factor(levels, _), Type::Char(Tchar::Val(level), _)) => levels.len() == 1 && levels[0] == *level,
+        _ => case.is_subtype(pattern, context)
+    }
+}
@@ -45,7 +45,7 @@ impl TryFrom<Lang> for Function {
 
     fn try_from(value: Lang) -> Result<Self, Self::Error> {
         match value {
-            Lang::Function(kinds, arg_types, return_type, body, help_data) 
+            Lang::Function(kinds, arg_types, _defaults, return_type, body, help_data) 
                 => Ok(Function::new(kinds, arg_types, return_type, body, help_data)),
             _ => Err(())
         }
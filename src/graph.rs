@@ -64,16 +64,21 @@ pub struct Graph {
 
 impl Graph {
 
-    /// Crée un nouveau graphe avec Generic comme nœud racine
-    pub fn new() -> Self {
+    /// Crée un graphe ne contenant que le nœud racine Generic
+    pub fn root_only() -> Self {
         let root_node = Rc::new(TypeNode::new(builder::generic_type()));
         let mut node_cache = HashMap::new();
         node_cache.insert(builder::generic_type(), root_node.clone());
 
-        let mut res = Graph {
+        Graph {
             root: root_node,
             node_cache,
-        };
+        }
+    }
+
+    /// Crée un nouveau graphe avec Generic comme nœud racine
+    pub fn new() -> Self {
+        let mut res = Graph::root_only();
 
         res.add_type(builder::integer_type_default());
         res.add_type(builder::character_type_default());
@@ -132,12 +137,12 @@ impl Graph {
         visited.insert(current.type_info.clone());
 
         // Vérifier si le nœud actuel est un super-type du nouveau type
-        if new_type.is_subtype(&current.type_info, &Context::default()) {
+        if new_type.is_subtype(&current.type_info, &Context::empty()) {
             // Vérifier les sous-types pour voir si certains sont des sous-types du nouveau type
             let mut subtypes_of_new_type = Vec::new();
             
             for subtype in current.subtypes.borrow().iter() {
-                if subtype.type_info.is_subtype(new_type, &Context::default()) {
+                if subtype.type_info.is_subtype(new_type, &Context::empty()) {
                     // Ce sous-type est un sous-type du nouveau type
                     subtypes_of_new_type.push(subtype.type_info.clone());
                 }
@@ -196,7 +201,7 @@ impl Graph {
         visited.insert(current.type_info.clone());
 
         // Vérifier si le nœud actuel est un super-type du nouveau type
-        if new_type.is_subtype(&current.type_info, &Context::default()) {
+        if new_type.is_subtype(&current.type_info, &Context::empty()) {
             // Si c'est un nœud feuille, c'est un point d'insertion
             if current.subtypes.borrow().is_empty() {
                 insertion_points.push(current.type_info.clone());
@@ -299,7 +304,7 @@ impl Graph {
         supertypes: &mut Vec<Type>
     ) {
         // Si le nœud actuel est un super-type du target
-        if target.is_subtype(&current.type_info, &Context::default()) && current.type_info != *target {
+        if target.is_subtype(&current.type_info, &Context::empty()) && current.type_info != *target {
             supertypes.push(current.type_info.clone());
         }
 
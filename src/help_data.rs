@@ -29,6 +29,16 @@ impl HelpData {
         }
     }
 
+    /// Line and column (both starting at 1) of the offset in its file
+    pub fn get_position(&self) -> Option<(usize, usize)> {
+        self.get_file_data().map(|(_, text)| {
+            let before = &text[..self.offset.min(text.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+            (line, column)
+        })
+    }
+
     pub fn random() -> Self {
         HelpData { offset: 7_usize, file_name: "asfdlwone".to_string() }
             
@@ -26,6 +26,30 @@ pub enum MsgTemplate<S: SourceCode + 'static + std::fmt::Debug> {
         #[help]
         help: Option<String>,
     },
+    #[error("Warning: {text}")]
+    #[diagnostic(severity(Warning))]
+    Warning {
+        text: String, 
+        #[label("{pos_text}")]
+        pos: SourceSpan,
+        pos_text: String,
+        #[source_code]
+        file: NamedSource<S>,
+        #[help]
+        help: Option<String>,
+    },
+    #[error("Note: {text}")]
+    #[diagnostic(severity(Advice))]
+    Note {
+        text: String, 
+        #[label("{pos_text}")]
+        pos: SourceSpan,
+        pos_text: String,
+        #[source_code]
+        file: NamedSource<S>,
+        #[help]
+        help: Option<String>,
+    },
     #[error("Type error: {text}")]
     Double {
         text: String, 
@@ -99,6 +123,28 @@ impl<S: SourceCode + 'static + std::fmt::Debug> SingleBuilder<S> {
         };
         Err(res.into())
     }
+
+    pub fn build_warning(self) -> Result<()> {
+        let res = MsgTemplate::Warning {
+            text: self.text,
+            pos: self.pos,
+            pos_text: self.pos_text,
+            file: self.file,
+            help: self.help,
+        };
+        Err(res.into())
+    }
+
+    pub fn build_note(self) -> Result<()> {
+        let res = MsgTemplate::Note {
+            text: self.text,
+            pos: self.pos,
+            pos_text: self.pos_text,
+            file: self.file,
+            help: self.help,
+        };
+        Err(res.into())
+    }
 }
 
 // Builder pour Double
@@ -180,7 +226,36 @@ pub enum TypeError {
     UndefinedVariable(Lang),
     UnmatchingReturnType(Type, Type),
     ImmutableVariable(Var, Var),
-    PrivateVariable(Var, Var)
+    PrivateVariable(Var, Var),
+    NonExhaustiveMatch(HelpData, Vec<Type>),
+    FieldNotFound((String, HelpData), Type),
+    WrongArity(HelpData, Type, usize),
+    WrongPattern(HelpData, Type),
+    MissingArgument(String, HelpData),
+    UnknownArgument(String, HelpData),
+    TooManyArguments(usize, HelpData),
+    WrongDefault(String, Type, Type),
+    IndexOutOfBounds(HelpData, Type, i32),
+    WrongIndex(HelpData, Type),
+    ImportCycle(Vec<String>, HelpData),
+    UnknownImport(String, String, HelpData),
+    UnsatisfiedBound(Type, Type, HelpData),
+    NullableArgument(Type, Type),
+    WrongColumns(Type, HelpData),
+    ColumnExpected(String, HelpData),
+    UnknownLevel(String, Type, HelpData),
+    DimensionMismatch(Type, Type, Type),
+    UnsolvableIndex(Type, Type),
+    UnsatisfiedCondition(Type, Type),
+    OpaqueConstruction(Type, Type, HelpData),
+    OpaqueAccess(Type, HelpData),
+    NotAnInterface(Type),
+    MissingMethod(String, Type, Type, HelpData),
+    UnknownMethod(String, Type, HelpData),
+    WrongMethod(String, Type, Type),
+    ConflictingImpl(Type, Type, HelpData),
+    AmbiguousCall(String, Vec<Type>, HelpData),
+    NoMatchingOverload(String, Vec<(Type, String)>, HelpData)
 }
 
 // main
@@ -190,7 +265,8 @@ impl ErrorMsg for TypeError {
             TypeError::Let(t1, t2) => {
                 let help_data1 = t1.get_help_data();
                 let help_data2 = t2.get_help_data();
-                let (file_name, text) = help_data1.get_file_data().unwrap();
+                let (file_name, text) = help_data1.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                 DoubleBuilder::new(file_name.clone(), text.clone(), file_name, text)
                     .pos1((help_data1.get_offset(), 0))
                     .pos2((help_data2.get_offset(), 1))
@@ -203,9 +279,9 @@ impl ErrorMsg for TypeError {
                 let help_data1 = t1.get_help_data();
                 let help_data2 = t2.get_help_data();
                 let (file_name1, text1) = help_data1.get_file_data()
-                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap()));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                 let (file_name2, text2) = help_data2.get_file_data()
-                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap()));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                     //.expect(&format!("The file name of {:?} for {} doesn't exist", 
                                      //help_data2, t2.pretty()));
                 DoubleBuilder::new(file_name1, text1, file_name2, text2)
@@ -220,11 +296,11 @@ impl ErrorMsg for TypeError {
                 let help_data1 = t1.get_help_data();
                 let help_data2 = t2.get_help_data();
                 let (file_name1, text1) = help_data1.get_file_data()
-                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap()));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                     //.expect(&format!("The file name of {:?} for {} doesn't exist", 
                                      //help_data1, t1.pretty()));
                 let (file_name2, text2) = help_data2.get_file_data()
-                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap()));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                     //.expect(&format!("The file name of {:?} for {} doesn't exist", 
                                      //help_data2, t2.pretty()));
                 DoubleBuilder::new(file_name1, text1, file_name2, text2)
@@ -265,18 +341,16 @@ impl ErrorMsg for TypeError {
                 let help_data1 = var_assign.get_help_data();
                 let help_data2 = var.get_help_data();
                 let (file_name1, text1) = help_data1.get_file_data()
-                    .expect(&format!("The file name of {:?} for {} doesn't exist", 
-                                     help_data1, var_assign));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                 let (file_name2, text2) = help_data2.get_file_data()
-                    .expect(&format!("The file name of {:?} for {} doesn't exist", 
-                                     help_data2, var));
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
                 DoubleBuilder::new(file_name1, text1, file_name2, text2)
                     .pos1((help_data1.get_offset(), 0))
                     .pos2((help_data2.get_offset(), 1))
                     .text(format!("The variable {} is immutable", var))
                     .pos_text1(format!("Forbidden assignation to {}", var))
-                    .pos_text2(format!("{} defined with 'let' (=immutable) here", var))
-                    .help("Try to replace the 'let' keyword by the 'mut' keyword")
+                    .pos_text2(format!("{} declared immutable here", var))
+                    .help("Declare it with 'let mut' to assign it again")
                     .build()
                 }
             TypeError::PrivateVariable(var_used, var)
@@ -299,12 +373,385 @@ impl ErrorMsg for TypeError {
                     .help("Try to add the 'pub' keyword befor the 'let' keyword")
                     .build()
                 }
+            TypeError::NonExhaustiveMatch(help_data, missing)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let missing_cases = missing.iter()
+                    .map(|t| format!("`{}`", t.pretty()))
+                    .collect::<Vec<_>>().join(", ");
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Non-exhaustive match: missing {}", missing_cases))
+                    .pos_text(format!("missing {}", missing_cases))
+                    .help("Add a branch for each missing case")
+                    .build()
+                }
+            TypeError::FieldNotFound((name, help_data), typ)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The field '{}' doesn't exist in {}", name, typ.pretty()))
+                    .pos_text(format!("Field '{}' not found", name))
+                    .build()
+                }
+            TypeError::WrongArity(help_data, typ, nb)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The pattern has {} elements but the value is of type {}", nb, typ.pretty()))
+                    .pos_text(format!("{} elements", nb))
+                    .build()
+                }
+            TypeError::WrongPattern(help_data, typ)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("A value of type {} can't be destructured by this pattern", typ.pretty()))
+                    .pos_text("Pattern")
+                    .help("Use {a, b} for records and (a, b) for tuples")
+                    .build()
+                }
+            TypeError::MissingArgument(name, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Missing argument '{}' in the function call", name))
+                    .pos_text(format!("'{}' has no default value", name))
+                    .build()
+                }
+            TypeError::UnknownArgument(name, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The function has no parameter named '{}'", name))
+                    .pos_text(format!("Unknown parameter '{}'", name))
+                    .build()
+                }
+            TypeError::TooManyArguments(nb, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Too many arguments: the function takes {}", nb))
+                    .pos_text("Extra argument".to_string())
+                    .build()
+                }
+            TypeError::IndexOutOfBounds(help_data, typ, position)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Index out of bounds: position {} in a value of type {}", position, typ.pretty()))
+                    .pos_text("Index out of bounds")
+                    .build()
+                }
+            TypeError::WrongIndex(help_data, typ)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("An array can't be indexed by a value of type {}", typ.pretty()))
+                    .pos_text(format!("Index of type {}", typ.pretty()))
+                    .help("Use an int, a range (2:5) or a logical mask")
+                    .build()
+                }
+            TypeError::WrongDefault(name, expected, received)
+                => {
+                let help_data = received.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The default value of '{}' is of type {} but the parameter is of type {}",
+                                  name, received.pretty(), expected.pretty()))
+                    .pos_text(format!("Expected {}", expected.pretty()))
+                    .build()
+                }
+            TypeError::ImportCycle(files, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Import cycle: {}", files.join(" -> ")))
+                    .pos_text("This import closes the cycle")
+                    .build()
+                }
+            TypeError::UnknownImport(name, module, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The module {} has no item named '{}'", module, name))
+                    .pos_text(format!("Unknown item '{}'", name))
+                    .build()
+                }
+            TypeError::UnsatisfiedBound(typ, bound, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The type {} doesn't implement {}", typ.pretty(), bound.pretty()))
+                    .pos_text(format!("Expected an implementation of {}", bound.pretty()))
+                    .build()
+                }
+            TypeError::NullableArgument(received, expected)
+                => {
+                let help_data = received.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The value of type {} can be NA where {} is expected", received.pretty(), expected.pretty()))
+                    .pos_text(format!("Check it with is.na before using it as {}", expected.pretty()))
+                    .build()
+                }
+            TypeError::WrongColumns(typ, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("A data frame is built from a record of arrays of the same length, not {}", typ.pretty()))
+                    .pos_text("Columns of different lengths")
+                    .build()
+                }
+            TypeError::ColumnExpected(verb, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("{} expects column names of its data", verb))
+                    .pos_text("Not a column")
+                    .help("Write the column names without quotes, or name = expression for a new column")
+                    .build()
+                }
+            TypeError::UnknownLevel(level, typ, help_data)
+                => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("'{}' isn't a level of {}", level, typ.pretty()))
+                    .pos_text("Unknown level")
+                    .build()
+                }
+            TypeError::UnsolvableIndex(expr, received) => {
+                let help_data = expr.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("Can't solve the index {} for a length of {}", expr.pretty2(), received.pretty2()))
+                    .pos_text(format!("Index {}", expr.pretty2()))
+                    .help("Only a linear index with one unknown generic (#N+1, 2*#N) and a length it can reach can be solved")
+                    .build()
+                }
+            TypeError::UnsatisfiedCondition(condition, received) => {
+                let help_data1 = condition.get_help_data();
+                let help_data2 = received.get_help_data();
+                let (file_name1, text1) = help_data1.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let (file_name2, text2) = help_data2.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                DoubleBuilder::new(file_name1, text1, file_name2, text2)
+                    .pos1((help_data1.get_offset(), 0))
+                    .pos2((help_data2.get_offset(), 1))
+                    .text(format!("The type {} doesn't satisfy {}", received.pretty(), condition.pretty()))
+                    .pos_text1(format!("Condition {}", condition.pretty()))
+                    .pos_text2(format!("Received {}", received.pretty()))
+                    .build()
+                }
+            TypeError::OpaqueConstruction(opaque, received, help_data2) => {
+                let help_data1 = opaque.get_help_data();
+                let (file_name1, text1) = help_data1.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let (file_name2, text2) = help_data2.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                DoubleBuilder::new(file_name1, text1, file_name2, text2)
+                    .pos1((help_data1.get_offset(), 0))
+                    .pos2((help_data2.get_offset(), 1))
+                    .text(format!("The type {} can't build the opaque type {}", received.pretty(), opaque.pretty()))
+                    .pos_text1(format!("Opaque type {}", opaque.pretty()))
+                    .pos_text2(format!("Received {}", received.pretty()))
+                    .help("An opaque type is only built inside its module: use the functions of the module")
+                    .build()
+                }
+            TypeError::OpaqueAccess(opaque, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The structure of the opaque type {} is private to its module", opaque.pretty()))
+                    .pos_text(format!("Access to {}", opaque.pretty()))
+                    .help("Use the functions of the module to inspect it")
+                    .build()
+                }
+            TypeError::NotAnInterface(typ) => {
+                let help_data = typ.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The type {} isn't an interface", typ.pretty()))
+                    .pos_text("Expected an interface")
+                    .build()
+                }
+            TypeError::MissingMethod(name, interface, typ, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The implementation of {} for {} lacks the method {}", interface.pretty(), typ.pretty(), name))
+                    .pos_text(format!("Missing {}", name))
+                    .help(format!("Add 'let {} <- fn(...)' to the impl block", name))
+                    .build()
+                }
+            TypeError::UnknownMethod(name, interface, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("{} isn't a method of {}", name, interface.pretty()))
+                    .pos_text(format!("Unknown method {}", name))
+                    .build()
+                }
+            TypeError::WrongMethod(name, expected, received) => {
+                let help_data1 = expected.get_help_data();
+                let help_data2 = received.get_help_data();
+                let (file_name1, text1) = help_data1.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let (file_name2, text2) = help_data2.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                DoubleBuilder::new(file_name1, text1, file_name2, text2)
+                    .pos1((help_data1.get_offset(), 0))
+                    .pos2((help_data2.get_offset(), 1))
+                    .text(format!("The method {} has the type {} instead of {}", name, received.pretty(), expected.pretty()))
+                    .pos_text1(format!("Expected {}", expected.pretty()))
+                    .pos_text2(format!("Found {}", received.pretty()))
+                    .build()
+                }
+            TypeError::ConflictingImpl(interface, typ, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("{} is already implemented for {}", interface.pretty(), typ.pretty()))
+                    .pos_text("Conflicting implementation")
+                    .help("A type has at most one implementation of an interface")
+                    .build()
+                }
+            TypeError::AmbiguousCall(name, candidates, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let candidates = candidates.iter()
+                    .map(|signature| signature.pretty())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("The call to {} is ambiguous between {}", name, candidates))
+                    .pos_text("Ambiguous call")
+                    .help("Annotate the arguments so that one signature is the most specific")
+                    .build()
+                }
+            TypeError::NoMatchingOverload(name, reasons, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let reasons = reasons.iter()
+                    .map(|(signature, reason)| format!("{}: {}", signature.pretty(), reason))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("No signature of {} matches the call", name))
+                    .pos_text("No matching overload")
+                    .help(reasons)
+                    .build()
+                }
+            TypeError::DimensionMismatch(dim, t1, t2) => {
+                let help_data1 = t1.get_help_data();
+                let help_data2 = t2.get_help_data();
+                let (file_name1, text1) = help_data1.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                let (file_name2, text2) = help_data2.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                DoubleBuilder::new(file_name1, text1, file_name2, text2)
+                    .pos1((help_data1.get_offset(), 0))
+                    .pos2((help_data2.get_offset(), 1))
+                    .text(format!("Mismatched dimensions: {} can't be both {} and {}", dim.pretty(), t1.pretty2(), t2.pretty2()))
+                    .pos_text1(format!("{} is {}", dim.pretty(), t1.pretty2()))
+                    .pos_text2(format!("{} is {}", dim.pretty(), t2.pretty2()))
+                    .build()
+                }
         };
         format!("{:?}", msg)
     }
 
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeWarning {
+    UnreachableArm(Type, Type),
+    DuplicateArm(Type),
+    InferredSignature(String, Type, HelpData), // a function without annotations
+}
+
+impl ErrorMsg for TypeWarning {
+    fn display(self) -> String {
+        let msg: Result<()> = match self {
+            TypeWarning::UnreachableArm(pattern, matched) => {
+                let help_data = pattern.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("unreachable match arm {}", pattern.pretty()))
+                    .pos_text(format!("{} can't be a value of {}", pattern.pretty(), matched.pretty()))
+                    .build_warning()
+            },
+            TypeWarning::DuplicateArm(pattern) => {
+                let help_data = pattern.get_help_data();
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("unreachable match arm {}", pattern.pretty()))
+                    .pos_text("Already covered by the previous arms")
+                    .help("Remove this arm")
+                    .build_warning()
+            },
+            TypeWarning::InferredSignature(name, typ, help_data) => {
+                let (file_name, text) = help_data.get_file_data()
+                    .unwrap_or(("std.ty".to_string(), fs::read_to_string("std.ty").unwrap_or_default()));
+                SingleBuilder::new(file_name, text)
+                    .pos((help_data.get_offset(), 0))
+                    .text(format!("inferred signature {}: {}", name, typ.pretty()))
+                    .pos_text(format!("{}: {}", name, typ.pretty()))
+                    .build_note()
+            },
+        };
+        format!("{:?}", msg)
+    }
+}
+
 pub enum SyntaxError {
     FunctionWithoutType(HelpData),
 }
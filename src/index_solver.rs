@@ -0,0 +1,154 @@
+use crate::Type;
+use crate::TypeError;
+use crate::tint::Tint;
+use crate::help_data::HelpData;
+use crate::help_message::ErrorMsg;
+
+// An index expression (#N+1-#M/#O) is kept as a linear form: a constant
+// plus a coefficient for each term. A term is an index generic or a
+// product/division that isn't linear (#M/#O), kept as it is.
+
+#[derive(Debug, Clone, PartialEq)]
+struct LinearForm {
+    constant: Tint,
+    terms: Vec<(Type, i32)>
+}
+
+impl LinearForm {
+    fn constant(value: Tint) -> LinearForm {
+        LinearForm { constant: value, terms: vec![] }
+    }
+
+    fn term(typ: Type) -> LinearForm {
+        LinearForm { constant: Tint::Val(0), terms: vec![(typ, 1)] }
+    }
+
+    fn add(self, other: LinearForm) -> LinearForm {
+        let terms = other.terms.into_iter()
+            .fold(self.terms, |terms, (typ, coef)| {
+                if terms.iter().any(|(t, _)| same_term(t, &typ)) {
+                    terms.into_iter()
+                        .map(|(t, c)| if same_term(&t, &typ) { (t, c + coef) } else { (t, c) })
+                        .collect()
+                } else {
+                    terms.into_iter().chain([(typ, coef)]).collect()
+                }
+            });
+        LinearForm {
+            constant: self.constant + other.constant,
+            terms: terms.into_iter().filter(|(_, coef)| *coef != 0).collect()
+        }
+    }
+
+    fn scale(self, factor: i32) -> LinearForm {
+        LinearForm {
+            constant: self.constant * Tint::Val(factor),
+            terms: self.terms.into_iter()
+                .map(|(typ, coef)| (typ, coef * factor))
+                .filter(|(_, coef)| *coef != 0)
+                .collect()
+        }
+    }
+
+    fn as_constant(&self) -> Option<Tint> {
+        self.terms.is_empty().then_some(self.constant)
+    }
+
+    fn to_type(&self, h: &HelpData) -> Type {
+        let integer = |i: i32| Type::Integer(Tint::Val(i), h.clone());
+        let terms = self.terms.iter()
+            .fold(None, |acc: Option<Type>, (typ, coef)| {
+                let (term, negative) = match coef {
+                    1 | -1 => (typ.clone(), *coef < 0),
+                    c => (Type::Mul(Box::new(integer(c.abs())), Box::new(typ.clone()), h.clone()), *c < 0)
+                };
+                Some(match (acc, negative) {
+                    (None, false) => term,
+                    (None, true) => Type::Minus(Box::new(integer(0)), Box::new(term), h.clone()),
+                    (Some(acc), false) => Type::Add(Box::new(acc), Box::new(term), h.clone()),
+                    (Some(acc), true) => Type::Minus(Box::new(acc), Box::new(term), h.clone())
+                })
+            });
+        match (terms, self.constant) {
+            (None, constant) => Type::Integer(constant, h.clone()),
+            (Some(terms), Tint::Val(0)) => terms,
+            (Some(terms), Tint::Val(c)) if c < 0
+                => Type::Minus(Box::new(terms), Box::new(integer(-c)), h.clone()),
+            (Some(terms), constant)
+                => Type::Add(Box::new(terms), Box::new(Type::Integer(constant, h.clone())), h.clone())
+        }
+    }
+}
+
+// the integers of a type are equal whatever their value
+fn same_term(typ1: &Type, typ2: &Type) -> bool {
+    typ1.pretty() == typ2.pretty()
+}
+
+fn linear_form(typ: &Type) -> Option<LinearForm> {
+    match typ {
+        Type::Integer(i, _) => Some(LinearForm::constant(*i)),
+        Type::IndexGen(_, _) => Some(LinearForm::term(typ.clone())),
+        Type::Add(a, b, _) => Some(linear_form(a)?.add(linear_form(b)?)),
+        Type::Minus(a, b, _) => Some(linear_form(a)?.add(linear_form(b)?.scale(-1))),
+        Type::Mul(a, b, h) => {
+            let (form1, form2) = (linear_form(a)?, linear_form(b)?);
+            match (form1.as_constant(), form2.as_constant()) {
+                (Some(Tint::Val(c)), _) => Some(form2.scale(c)),
+                (_, Some(Tint::Val(c))) => Some(form1.scale(c)),
+                (Some(Tint::Unknown), _) | (_, Some(Tint::Unknown)) => Some(LinearForm::constant(Tint::Unknown)),
+                _ => Some(LinearForm::term(Type::Mul(Box::new(form1.to_type(h)), Box::new(form2.to_type(h)), h.clone())))
+            }
+        },
+        Type::Div(a, b, h) => {
+            let (form1, form2) = (linear_form(a)?, linear_form(b)?);
+            match (form1.as_constant(), form2.as_constant()) {
+                (Some(i), Some(j)) => Some(LinearForm::constant(i / j)),
+                _ => Some(LinearForm::term(Type::Div(Box::new(form1.to_type(h)), Box::new(form2.to_type(h)), h.clone())))
+            }
+        },
+        _ => None
+    }
+}
+
+pub fn is_index_expression(typ: &Type) -> bool {
+    linear_form(typ).is_some()
+}
+
+/// #N+1-1 gives #N and 2*#N+#N gives 3*#N
+pub fn simplify(typ: &Type) -> Type {
+    linear_form(typ)
+        .map(|form| form.to_type(&typ.get_help_data()))
+        .unwrap_or(typ.clone())
+}
+
+/// Values of the index generics of expr (a parameter's type) for the
+/// received length: [#N+1, T] given a [5, T] has #N = 4
+pub fn solve(expr: &Type, received: &Type) -> Option<Vec<(Type, Type)>> {
+    let h = received.get_help_data();
+    let form = linear_form(received)?.add(linear_form(expr)?.scale(-1));
+    let unknowns = form.terms.iter()
+        .filter(|(typ, coef)| matches!(typ, Type::IndexGen(_, _)) && *coef < 0)
+        .collect::<Vec<_>>();
+    match (unknowns.as_slice(), form.as_constant()) {
+        ([], Some(Tint::Val(0))) | ([], Some(Tint::Unknown)) => Some(vec![]),
+        ([], Some(_)) => None,
+        // received = expr, so the unknown is the rest of the difference divided by its coefficient
+        ([(generic, coef)], _) => {
+            let rest = LinearForm {
+                constant: form.constant,
+                terms: form.terms.iter().filter(|(typ, _)| !same_term(typ, generic)).cloned().collect()
+            };
+            let coef = -coef;
+            let value = match (rest.as_constant(), coef) {
+                // a length is never negative
+                (Some(Tint::Val(c)), _) if c % coef == 0 && c / coef >= 0 => Type::Integer(Tint::Val(c / coef), h),
+                (Some(Tint::Unknown), _) => Type::Integer(Tint::Unknown, h),
+                (None, 1) => rest.to_type(&h),
+                _ => panic!("{}", TypeError::UnsolvableIndex(expr.clone(), received.clone()).display())
+            };
+            Some(vec![(value, generic.clone())])
+        },
+        _ => panic!("{}", TypeError::UnsolvableIndex(expr.clone(), received.clone()).display())
+    }
+}
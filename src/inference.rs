@@ -0,0 +1,161 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::var::Var;
+use crate::argument_type::ArgumentType;
+use crate::argument_value::ArgumentValue;
+use crate::unification::unify;
+use crate::unification_map::UnificationMap;
+use crate::type_checker::typing;
+use crate::type_checker::eval;
+use crate::builder;
+use crate::help_data::HelpData;
+use crate::unification::type_substitution;
+
+// fn(x, y) { ... }: each parameter without annotation gets a type variable,
+// solved from the way the body uses it. What stays unsolved becomes a generic.
+
+fn type_variable(name: &str) -> Type {
+    Type::Generic(format!("?{}", name), HelpData::default())
+}
+
+fn is_type_variable(typ: &Type) -> bool {
+    matches!(typ, Type::Generic(name, _) if name.starts_with('?'))
+}
+
+pub fn needs_inference(params: &[ArgumentType], ret_ty: &Type) -> bool {
+    ret_ty.is_empty() || params.iter().any(|arg_typ| arg_typ.get_type().is_empty())
+}
+
+fn with_params(context: &Context, params: &[ArgumentType], solution: &UnificationMap) -> Context {
+    params.iter()
+        .map(|arg_typ| {
+            let typ = solution.type_substitution(&arg_typ.get_type());
+            (arg_typ.clone().set_type(typ.clone()).to_var(context), typ)
+        })
+        .fold(context.clone(), |cont, (var, typ)| cont.clone().push_var_type(var, typ, &cont))
+}
+
+// a parameter keeps its type variable, anything else is typed with what is already solved
+fn value_type(context: &Context, value: &Lang, params: &[ArgumentType]) -> Type {
+    match value {
+        Lang::Variable(name, path, _, _, _, _) if path.is_empty() => params.iter()
+            .find(|arg_typ| arg_typ.get_argument_str() == *name)
+            .map(ArgumentType::get_type)
+            .unwrap_or_else(|| typing(context, value).0),
+        _ => typing(context, value).0
+    }
+}
+
+// each argument of a call must fit the parameter it is given to
+fn call_constraints(context: &Context, fn_name: &Lang, values: &[Lang], params: &[ArgumentType], h: &HelpData) -> Vec<(Type, Type)> {
+    let var = Var::try_from(Box::new(fn_name.clone())).unwrap();
+    if context.is_an_untyped_function(&var.get_name()) {
+        return vec![]
+    }
+    let values = context.get_parameters(&var.get_name())
+        .map(|fn_params| fn_params.arrange(values, h))
+        .unwrap_or(values.to_vec());
+    let types = values.iter()
+        .map(|value| value_type(context, value, params))
+        .collect::<Vec<_>>();
+    // the dispatch on the first argument can't be done before its type is known
+    let func = match types.first() {
+        Some(typ) if is_type_variable(typ) => context.variables()
+            .find(|(v, _)| v.get_name() == var.get_name() && v.get_path() == var.get_path())
+            .and_then(|(_, fn_typ)| fn_typ.to_function_type()),
+        _ => fn_name.clone().get_related_function(&values, context)
+    };
+    func.map(|func| types.iter()
+            .zip(func.get_param_types())
+            .flat_map(|(typ, param_typ)| unify(context, typ, &param_typ))
+            .collect())
+        .unwrap_or_default()
+}
+
+fn constraints(context: &Context, expr: &Lang, params: &[ArgumentType], found: Vec<(Type, Type)>) -> Vec<(Type, Type)> {
+    match expr {
+        Lang::FunctionApp(fn_name, values, _, h) => {
+            let found = values.iter()
+                .fold(found, |acc, value| constraints(context, value, params, acc));
+            let solved = with_params(context, params, &solve(&found));
+            let calls = call_constraints(&solved, fn_name, values, params, h);
+            found.into_iter().chain(calls).collect()
+        },
+        Lang::If(cond, true_branch, false_branch, _) => {
+            let found = constraints(context, cond, params, found);
+            let solved = with_params(context, params, &solve(&found));
+            let cond_constraints = unify(&solved, &typing(&solved, cond).0, &builder::boolean_type());
+            [true_branch, false_branch].iter()
+                .fold(found.into_iter().chain(cond_constraints).collect(),
+                    |acc, e| constraints(&solved, e, params, acc))
+        },
+        Lang::Sequence(exprs, _) | Lang::Scope(exprs, _) => {
+            exprs.iter().fold((context.clone(), found), |(cont, acc), e| {
+                let acc = constraints(&cont, e, params, acc);
+                let solved = with_params(&cont, params, &solve(&acc));
+                (eval(&solved, e), acc)
+            }).1
+        },
+        Lang::Array(exprs, _) | Lang::Tuple(exprs, _) => exprs.iter()
+            .fold(found, |acc, e| constraints(context, e, params, acc)),
+        Lang::Let(_, _, body, _) | Lang::Return(body, _) => constraints(context, body, params, found),
+        Lang::ArrayIndexing(e1, e2, _) | Lang::ListIndexing(e1, e2, _) => {
+            let found = constraints(context, e1, params, found);
+            constraints(context, e2, params, found)
+        },
+        _ => found
+    }
+}
+
+fn solve(found: &[(Type, Type)]) -> UnificationMap {
+    UnificationMap::new(found.iter()
+        .filter(|(var, typ)| is_type_variable(var) && !is_type_variable(typ))
+        .cloned()
+        .collect())
+}
+
+fn fresh_generic_names(taken: &[Type]) -> impl Iterator<Item = String> + '_ {
+    ["T", "U", "V", "W", "X", "Y", "Z"].into_iter()
+        .map(str::to_string)
+        .chain((1..).map(|i| format!("T{}", i)))
+        .filter(move |name| !taken.iter().any(|t| matches!(t, Type::Generic(n, _) if n == name)))
+}
+
+/// Parameters and return type of the function once the missing ones are inferred
+pub fn infer_function(context: &Context, params: &[ArgumentType], defaults: &[ArgumentValue], ret_ty: &Type, body: &Lang) -> (Vec<ArgumentType>, Type) {
+    let params = params.iter()
+        .map(|arg_typ| match arg_typ.get_type() {
+            Type::Empty(_) => arg_typ.clone().set_type(type_variable(&arg_typ.get_argument_str())),
+            _ => arg_typ.clone()
+        }).collect::<Vec<_>>();
+    let from_defaults = defaults.iter()
+        .map(|arg_val| (type_variable(&arg_val.get_argument()), typing(context, &arg_val.get_value()).0.generalize()))
+        .filter(|(var, _)| params.iter().any(|arg_typ| arg_typ.get_type() == *var));
+    let found = constraints(&with_params(context, &params, &UnificationMap::new(vec![])), body, &params, from_defaults.collect());
+    let solution = solve(&found);
+    let solved = params.iter()
+        .map(|arg_typ| arg_typ.clone().set_type(solution.type_substitution(&arg_typ.get_type())))
+        .collect::<Vec<_>>();
+    let taken = solved.iter()
+        .map(ArgumentType::get_type)
+        .chain([ret_ty.clone()])
+        .flat_map(|typ| typ.extract_generics())
+        .collect::<Vec<_>>();
+    let generics = solved.iter()
+        .map(ArgumentType::get_type)
+        .filter(is_type_variable)
+        .zip(fresh_generic_names(&taken))
+        .map(|(var, name)| (var.clone(), Type::Generic(name, var.get_help_data())))
+        .collect::<Vec<_>>();
+    let new_params = solved.into_iter()
+        .map(|arg_typ| {
+            let typ = type_substitution(&arg_typ.get_type(), &generics);
+            arg_typ.set_type(typ)
+        }).collect::<Vec<_>>();
+    let new_ret = if ret_ty.is_empty() {
+        let sub_context = with_params(context, &new_params, &UnificationMap::new(vec![]));
+        typing(&sub_context, body).0.reduce(&sub_context).generalize()
+    } else { ret_ty.clone() };
+    (new_params, new_ret)
+}
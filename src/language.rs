@@ -11,6 +11,7 @@ use crate::typing;
 use crate::help_data::HelpData;
 use crate::path::Path;
 use crate::function_type::FunctionType;
+use crate::nullable::non_null;
 use crate::type_comparison::reduce_type;
 use crate::translatable::Translatable;
 use crate::function_lang::Function;
@@ -18,6 +19,9 @@ use crate::array_type::ArrayType;
 use crate::translatable::RTranslatable;
 use crate::builder;
 use crate::r#type::display_types;
+use crate::pattern::Pattern;
+use crate::parameters::Parameters;
+use crate::tchar::Tchar;
 
 trait AndIf {
     fn and_if<F>(self, condition: F) -> Option<Self>
@@ -55,6 +59,7 @@ pub enum Lang {
     Integer(i32, HelpData),
     Bool(bool, HelpData),
     Char(String, HelpData),
+    Na(HelpData), // the missing value NA
     And(Box<Lang>, Box<Lang>, HelpData),
     Or(Box<Lang>, Box<Lang>, HelpData),
     Union(Box<Lang>, Box<Lang>, HelpData),
@@ -70,13 +75,15 @@ pub enum Lang {
     GreaterOrEqual(Box<Lang>, Box<Lang>, HelpData),
     Chain(Box<Lang>, Box<Lang>, HelpData),
     Scope(Vec<Lang>, HelpData),
-    Function(Vec<ArgumentKind>, Vec<ArgumentType>, Type, Box<Lang>, HelpData),
+    Function(Vec<ArgumentKind>, Vec<ArgumentType>, Vec<ArgumentValue>, Type, Box<Lang>, HelpData), // defaults after the params
     Module(String, Vec<Lang>, HelpData), // module name { lines }
     ModuleDecl(String, HelpData), // to create an env
     Variable(String, Path, Permission, bool, Type, HelpData),
     FunctionApp(Box<Lang>, Vec<Lang>, Type, HelpData),
-    ArrayIndexing(Box<Lang>, i32, HelpData),
+    ArrayIndexing(Box<Lang>, Box<Lang>, HelpData),
+    ListIndexing(Box<Lang>, Box<Lang>, HelpData), // xs[[i + 1]]
     Let(Var, Type, Box<Lang>, HelpData),
+    Destructure(Pattern, Type, Box<Lang>, HelpData), // let {x, y} <- point;
     Array(Vec<Lang>, HelpData),
     Record(Vec<ArgumentValue>, HelpData),
     Alias(Var, Vec<Type>, Type, HelpData),
@@ -87,35 +94,54 @@ pub enum Lang {
     Sequence(Vec<Lang>, HelpData),
     Assign(Box<Lang>, Box<Lang>, HelpData),
     Comment(String, HelpData),
-    ModImp(String, HelpData), // mod name;
-    Import(Type, HelpData), // type alias
+    ModImp(String, HelpData), // mod a::b; (file a/b.ty)
+    Use(String, Option<Vec<String>>, bool, HelpData), // [pub] use module::{names}; (None for a glob)
+    Impl(Type, Type, Vec<Lang>, HelpData), // impl Interface for Type { methods }
     GenFunc(String, String, HelpData), //body, name, helpdata
     Test(Vec<Lang>, HelpData),
     Return(Box<Lang>, HelpData),
     VecBloc(String, HelpData),
-    Lambda(Box<Lang>, HelpData),
+    Lambda(Vec<ArgumentType>, Box<Lang>, HelpData), // untyped params are Empty
+    Formula(String, Vec<String>, HelpData), // R text and the variables it uses
     Library(String, HelpData),
     Exp(String, HelpData),
-    Signature(Var, Type, HelpData),
+    Signature(Var, Type, Parameters, HelpData),
     ForLoop(Var, Box<Lang>, Box<Lang>, HelpData), // variable, iterator, body
     RFunction(Vec<Lang>, String, HelpData), // variable, iterator, body
     Empty(HelpData)
 }
 
+// A Lang::Variable names a value and only the name of a type alias is
+// opaque: aliases stay Vars in Lang::Alias and Type::Alias, so there is no
+// opacity to keep here (see Var::from_language).
 impl From<Var> for Lang {
    fn from(val: Var) -> Self {
-       Lang::Variable(val.0, val.1.into(), val.2, val.3, val.4, val.5)
+       Lang::Variable(val.0, val.1.into(), val.2, val.3, val.5, val.6)
    } 
 }
 
-pub fn build_generic_function(s: &str) -> Lang {
-    builder::generic_function(
-        &format!("{} <- function(x, ...) {{\n\tUseMethod('{}')\n}}\n", s, s)
-                                                         )
+pub fn build_generic_function(var: &Var) -> Lang {
+    let s = var.get_name();
+    if var.is_from_other_module() {
+        // the generic of a module lives in its environment, where its methods are registered
+        let env = var.1.get_value().replace("::", "$");
+        builder::generic_function(
+            &format!("{0}${1} <- function(x, ...) {{\n\tUseMethod('{1}')\n}}\nenvironment({0}${1}) <- {0}\n", env, s))
+    } else {
+        builder::generic_function(
+            &format!("{} <- function(x, ...) {{\n\tUseMethod('{}')\n}}\n", s, s)
+                                                             )
+    }
 }
 
 fn condition_to_if(var: &Var, typ: &Type, context: &Context) -> String {
-    format!("any(class({}) == c({}))", var.get_name(), context.get_class(typ))
+    match typ {
+        Type::Tag(name, _, _) 
+            => format!("inherits({0}, 'Tag') && {0}[[1]] == '{1}'", var.get_name(), name),
+        Type::Char(Tchar::Val(level), _)
+            => format!("{} == '{}'", var.get_name(), level),
+        _ => format!("any(class({}) == c({}))", var.get_name(), context.get_class(typ))
+    }
 }
 
 fn to_if_statement(var: Var, exp: Lang, branches: &[(Type, Box<Lang>)], context: &Context) -> String {
@@ -156,7 +182,7 @@ impl Lang {
 
 
     pub fn is_undefined(&self) -> bool {
-        if let Lang::Function(_, _, _, body, _h) = self.clone() {
+        if let Lang::Function(_, _, _, _, body, _h) = self.clone() {
             if let Lang::Scope(v, _) = *body.clone() {
                    let ele = v.first().unwrap();
                    if let Lang::Empty(_) = ele {true} else {false}
@@ -164,27 +190,45 @@ impl Lang {
         } else {false}
     }
 
+    /// The _ of a pipeline stage: x |> f(y, _)
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self, Lang::Variable(name, _, _, _, _, _) if name == "_")
+    }
+
+    pub fn get_parameters(&self) -> Parameters {
+        match self {
+            Lang::Function(_, args, defaults, _, _, _) => Parameters::new(args, defaults),
+            _ => Parameters::default()
+        }
+    }
+
     pub fn is_function(&self) -> bool {
         match self {
-            Lang::Function(_, _, _, _, _) => true,
+            Lang::Function(_, _, _, _, _, _) => true,
             Lang::RFunction(_, _, _) => true,
             _ => false
         }
     }
 
-    pub fn infer_var_name(&self, args: &Vec<Lang>, context: &Context) -> Var {
-        if args.len() > 0 {
-                        let first = typing(context, &args.iter().nth(0).unwrap().clone()).0;
-                        Var::from_language(self.clone())
-                            .unwrap().set_type(first, context)
-                    } else {
-                        Var::from_language(self.clone()).unwrap()
-            }
+    pub fn infer_var_name(&self, first: Option<Type>, context: &Context) -> Var {
+        match first {
+            // an int? is dispatched as an int, its NA is checked against the parameters
+            Some(first) => Var::from_language(self.clone())
+                            .unwrap().set_type(non_null(first), context),
+            None => Var::from_language(self.clone()).unwrap()
+        }
     }
 
     pub fn get_related_function(self, args: &Vec<Lang>, context: &Context) 
         -> Option<FunctionType> {
-        let var_name = self.infer_var_name(args, context);
+        let first = args.first().map(|arg| typing(context, arg).0);
+        self.get_dispatched_function(first, context)
+    }
+
+    /// The function dispatched on the type of the first argument, when it is already known
+    pub fn get_dispatched_function(self, first: Option<Type>, context: &Context) 
+        -> Option<FunctionType> {
+        let var_name = self.infer_var_name(first, context);
         let fn_ty = typing(context, &var_name.to_language()).0;
         fn_ty.to_function_type()
     }
@@ -206,6 +250,7 @@ impl Lang {
             Lang::Integer(_, h) => h,
             Lang::Char(_, h) => h,
             Lang::Bool(_, h) => h,
+            Lang::Na(h) => h,
             Lang::And(_, _, h) => h,
             Lang::Or(_, _, h) => h,
             Lang::Union(_, _, h) => h,
@@ -221,13 +266,16 @@ impl Lang {
             Lang::GreaterOrEqual(_, _, h) => h,
             Lang::Chain(_, _, h) => h,
             Lang::Scope(_, h) => h,
-            Lang::Function(_, _, _, _, h) => h,
+            Lang::Function(_, _, _, _, _, h) => h,
             Lang::Module(_, _, h) => h,
             Lang::ModuleDecl(_, h) => h,
+            Lang::Impl(_, _, _, h) => h,
             Lang::Variable(_, _, _, _, _, h) => h,
             Lang::FunctionApp(_, _, _, h) => h,
             Lang::ArrayIndexing(_, _, h) => h,
+            Lang::ListIndexing(_, _, h) => h,
             Lang::Let(_, _, _, h) => h,
+            Lang::Destructure(_, _, _, h) => h,
             Lang::Array(_, h) => h,
             Lang::Record(_, h) => h,
             Lang::Alias(_, _, _, h) => h,
@@ -239,16 +287,17 @@ impl Lang {
             Lang::Assign(_, _, h) => h,
             Lang::Comment(_, h) => h,
             Lang::ModImp(_, h) => h,
-            Lang::Import(_, h) => h,
+            Lang::Use(_, _, _, h) => h,
             Lang::GenFunc(_, _, h) => h,
             Lang::Test(_, h) => h,
             Lang::Return(_, h) => h,
             Lang::VecBloc(_, h) => h,
-            Lang::Lambda(_, h) => h,
+            Lang::Lambda(_, _, h) => h,
+            Lang::Formula(_, _, h) => h,
             Lang::Library(_, h) => h,
             Lang::Exp(_, h) => h,
             Lang::Empty(h) => h,
-            Lang::Signature(_, _, h) => h,
+            Lang::Signature(_, _, _, h) => h,
             Lang::ForLoop(_, _, _, h) => h,
             Lang::RFunction(_, _, h) => h,
         }.clone()
@@ -276,7 +325,7 @@ impl Lang {
     pub fn nb_params(&self) -> usize {
         self.simple_print();
         match self {
-            Lang::Function(_, params, _, _, _) => params.len(),
+            Lang::Function(_, params, _, _, _, _) => params.len(),
             _ => 0 as usize
         }
     }
@@ -287,6 +336,7 @@ impl Lang {
             Lang::Integer(_, _) => "Integer".to_string(),
             Lang::Char(_, _) => "Char".to_string(),
             Lang::Bool(_, _) => "Bool".to_string(),
+            Lang::Na(_) => "NA".to_string(),
             Lang::And(_, _, _) => "And".to_string(),
             Lang::Or(_, _, _) => "Or".to_string(),
             Lang::Union(_, _, _) => "Union".to_string(),
@@ -302,14 +352,17 @@ impl Lang {
             Lang::GreaterOrEqual(_, _, _) => "GreatOrEqual".to_string(),
             Lang::Chain(_, _, _) => "Chain".to_string(),
             Lang::Scope(_, _) => "Scope".to_string(),
-            Lang::Function(_, _, _, _, _) => "Function".to_string(),
+            Lang::Function(_, _, _, _, _, _) => "Function".to_string(),
             Lang::Module(_, _, _) => "Module".to_string(),
             Lang::ModuleDecl(_, _) => "ModuleDecl".to_string(),
+            Lang::Impl(_, _, _, _) => "Impl".to_string(),
             Lang::Variable(name, _, _, _, _, _) => format!("Variable({})", name),
             Lang::FunctionApp(var, _, _, _) => 
                 format!("FunctionApp({})", Var::from_language(*(var.clone())).unwrap().get_name()),
             Lang::ArrayIndexing(_, _, _) => "ArrayIndexing".to_string(),
+            Lang::ListIndexing(_, _, _) => "ListIndexing".to_string(),
             Lang::Let(var, _, _, _) => format!("let {}", var.get_name()),
+            Lang::Destructure(_, _, _, _) => "Destructure".to_string(),
             Lang::Array(_, _) => "Array".to_string(),
             Lang::Record(_, _) => "Record".to_string(),
             Lang::Alias(_, _, _, _) => "Alias".to_string(),
@@ -321,16 +374,17 @@ impl Lang {
             Lang::Assign(_, _, _) => "Addign".to_string(),
             Lang::Comment(_, _) => "Comment".to_string(),
             Lang::ModImp(_, _) => "ModImp".to_string(),
-            Lang::Import(_, _) => "Import".to_string(),
+            Lang::Use(_, _, _, _) => "Use".to_string(),
             Lang::GenFunc(_, _, _) => "GenFunc".to_string(),
             Lang::Test(_, _) => "Test".to_string(),
             Lang::Return(_, _) => "Return".to_string(),
             Lang::VecBloc(_, _) => "VecBloc".to_string(),
-            Lang::Lambda(_, _) => "Lambda".to_string(),
+            Lang::Lambda(_, _, _) => "Lambda".to_string(),
+            Lang::Formula(text, _, _) => format!("formula({})", text),
             Lang::Library(_, _) => "Library".to_string(),
             Lang::Exp(_, _) => "Exp".to_string(),
             Lang::Empty(_) => "Empty".to_string(),
-            Lang::Signature(_, _, _) => "Signature".to_string(),
+            Lang::Signature(_, _, _, _) => "Signature".to_string(),
             Lang::ForLoop(_, _, _, _) => "ForLoop".to_string(),
             Lang::RFunction(_, _, _) => "RFunction".to_string(),
         }
@@ -348,6 +402,7 @@ impl From<Lang> for HelpData {
            Lang::Number(_, h) => h,
            Lang::Integer(_, h) => h,
            Lang::Bool(_, h) => h,
+           Lang::Na(h) => h,
            Lang::Char(_, h) => h,
            Lang::Variable(_, _, _, _, _, h) => h,
            Lang::Match(_, _, _, h) => h,
@@ -361,9 +416,11 @@ impl From<Lang> for HelpData {
            Lang::Record(_, h) => h,
            Lang::Scope(_, h) => h,
            Lang::Let(_, _, _, h) => h,
+           Lang::Destructure(_, _, _, h) => h,
            Lang::Alias(_, _, _, h) => h,
-           Lang::Lambda(_, h) => h,
-           Lang::Function(_, _, _, _, h) => h,
+           Lang::Lambda(_, _, h) => h,
+           Lang::Formula(_, _, h) => h,
+           Lang::Function(_, _, _, _, _, h) => h,
            Lang::VecBloc(_, h) => h,
            Lang::If(_, _, _, h) => h,
            Lang::Assign(_, _, h) => h,
@@ -375,13 +432,15 @@ impl From<Lang> for HelpData {
            Lang::Modu2(_, _, h) => h,
            Lang::Module(_, _, h) => h,
            Lang::ModuleDecl(_, h) => h,
+           Lang::Impl(_, _, _, h) => h,
            Lang::ModImp(_, h) => h,
-           Lang::Import(_, h) => h,
+           Lang::Use(_, _, _, h) => h,
            Lang::GreaterThan(_, _, h) => h,
            Lang::GreaterOrEqual(_, _, h) => h,
            Lang::LesserThan(_, _, h) => h,
            Lang::LesserOrEqual(_, _, h) => h,
            Lang::ArrayIndexing(_, _, h) => h,
+           Lang::ListIndexing(_, _, h) => h,
            Lang::Tag(_, _, h) => h,
            Lang::Tuple(_, h) => h,
            Lang::Sequence(_, h) => h,
@@ -391,7 +450,7 @@ impl From<Lang> for HelpData {
            Lang::Return(_, h) => h,
            Lang::Library(_, h) => h,
            Lang::Exp(_, h) => h,
-           Lang::Signature(_, _, h) => h,
+           Lang::Signature(_, _, _, h) => h,
            Lang::ForLoop(_, _, _, h) => h,
            Lang::RFunction(_, _, h) => h,
        }.clone()
@@ -424,6 +483,7 @@ impl RTranslatable<(String, Context)> for Lang {
         let result = match self {
             Lang::Bool(b, _) => 
                 (format!("{}", b.to_string().to_uppercase()), cont.clone()),
+            Lang::Na(_) => ("NA".to_string(), cont.clone()),
             Lang::In(b1, b2, _) => {
                 Translatable::from(cont.clone())
                     .to_r(b1).add(" %in% ").to_r(b2).into()
@@ -495,7 +555,7 @@ impl RTranslatable<(String, Context)> for Lang {
                     _ => {
                         Translatable::from(cont.clone())
                             .to_r(e2).add("[[")
-                            .add("]]").to_r(e1)
+                            .to_r(e1).add("]]")
                             .into()
                     }
                 }
@@ -504,7 +564,12 @@ impl RTranslatable<(String, Context)> for Lang {
                 Translatable::from(cont.clone())
                     .join(exps, "\n").into()
             },
-            Lang::Function(_args_kind, args, _typ, body, _h) => {
+            // the methods of an impl block are S3 methods of the type
+            Lang::Impl(_, _, methods, _) => {
+                Translatable::from(cont.clone())
+                    .join(methods, "\n").into()
+            },
+            Lang::Function(_args_kind, args, defaults, _typ, body, _h) => {
                 //Wasn't able to use Translatable
                 let sub_cont = cont.add_arg_types(args);
                 let (body_str, new_cont) = body.to_r(&sub_cont);
@@ -512,8 +577,15 @@ impl RTranslatable<(String, Context)> for Lang {
                 //let class = cont.get_class(&fn_type);
                 //let classes = cont.get_classes(&fn_type)
                     //.unwrap_or("''".to_string());
+                let params = args.iter()
+                    .map(|arg_typ| {
+                        defaults.iter()
+                            .find(|arg_val| arg_val.get_argument() == arg_typ.to_r())
+                            .map(|arg_val| arg_val.to_r(cont))
+                            .unwrap_or(arg_typ.to_r())
+                    }).collect::<Vec<_>>();
                 (format!("(function({}) {{\n {} \n}}) |> {}", 
-                        args.iter().map(|x| x.to_r()).collect::<Vec<_>>().join(", "),
+                        params.join(", "),
                         body_str, cont.get_type_anotation(&fn_type)), 
                 new_cont)
             },
@@ -542,8 +614,11 @@ impl RTranslatable<(String, Context)> for Lang {
                 } else {
                     let (exp_str, cont1) = exp.to_r(cont);
                     let fn_t = cont1.get_true_fn_type(self)
-                                    .unwrap_or(
-                                        FunctionType::try_from(cont1.get_type_from_variable(&var).expect(&format!("variable {} don't have a related type", var))).unwrap());
+                                    .or_else(|| cont1.get_type_from_variable(&var)
+                                             .map(|typ| FunctionType::try_from(typ).unwrap()))
+                                    // the functions of a module are found like in the type checker
+                                    .or_else(|| exp.clone().get_related_function(vals, &cont1))
+                                    .unwrap_or_else(|| panic!("variable {} don't have a related type", var));
 
                     let new_args = fn_t.get_param_types().into_iter()
                             .map(|arg| reduce_type(&cont1, &arg))
@@ -559,7 +634,7 @@ impl RTranslatable<(String, Context)> for Lang {
                     
                     Var::from_language(*exp.clone())
                         .map(|var| {
-                            let (name, path) = (var.get_name(), Path::new(&var.get_path()));
+                            let (name, path) = (var.get_name(), var.1.clone());
                             let new_name = if &name[0..1] == "%" {
                                 format!("`{}`", name.replace("__", "."))
                             } else { name.replace("__", ".") };
@@ -570,32 +645,44 @@ impl RTranslatable<(String, Context)> for Lang {
                         }).unwrap_or((format!("{}({})", exp_str, args), current_cont))
                 }
             },
-            Lang::ArrayIndexing(exp, val, _) => {
-                let (exp_str, new_cont) = exp.to_r(cont);
-                (format!("{}[{}]", exp_str, val), new_cont)
+            Lang::ArrayIndexing(exp, index, _) => {
+                Translatable::from(cont.clone())
+                    .to_r(exp).add("[").to_r(index).add("]").into()
+            },
+            Lang::ListIndexing(exp, index, _) => {
+                Translatable::from(cont.clone())
+                    .to_r(exp).add("[[").to_r(index).add("]]").into()
             },
             Lang::GenFunc(func, _, _) => 
                 (func.to_string(), cont.clone()),
             Lang::Let(var, ttype, body, _) => {
-                let (body_str, new_cont) = body.to_r(cont);
+                let scope = cont.open_module(&var.get_path());
+                let (body_str, new_cont) = if var.is_from_other_module() {
+                    (body.to_r(&scope).0, cont.clone())
+                } else { body.to_r(&scope) };
                 let new_name = var.clone().to_r(cont);
 
-                let (r_code, _new_name2) =
+                let (r_code, method_class) =
                 Function::try_from((**body).clone())
                     .map(|_| {
                         let related_type = var.get_type();
                         match related_type {
                             Type::Empty(_) 
-                                => (format!("{} <- {}", new_name, body_str), new_name.clone()),
+                                => (format!("{} <- {}", new_name, body_str), None),
                             Type::Any(_) | Type::Generic(_, _) 
-                                => (format!("{}.default <- {}", new_name, body_str), new_name.clone()),
+                                => (format!("{}.default <- {}", new_name, body_str), Some("default".to_string())),
                             _ => {
-                                let class = cont.get_class_unquoted(&reduce_type(cont, &related_type));
+                                let reduced = reduce_type(cont, &related_type);
+                                // a recursive or opaque type is out of the hierarchy: its values have its name
+                                let class = match &related_type {
+                                    Type::Alias(name, _, _, _, _) if reduced.keeps_alias() => name.clone(),
+                                    _ => cont.get_class_unquoted(&reduced)
+                                };
                                 let new_name2 = format!("{}.{}", new_name.clone(), class);
-                                (format!("{} <- {}", new_name2, body_str), new_name2)
+                                (format!("{} <- {}", new_name2, body_str), Some(class))
                             }
                         }
-                    }).unwrap_or((format!("{} <- {}", new_name, body_str), new_name));
+                    }).unwrap_or((format!("{} <- {}", new_name, body_str), None));
                 let code = if !ttype.is_empty() {
                     let anotation = new_cont.get_type_anotation(ttype);
                     if anotation == "Generic()" {
@@ -606,9 +693,23 @@ impl RTranslatable<(String, Context)> for Lang {
                 } else {
                     r_code + "\n"
                 };
-                (code, new_cont)
+                // UseMethod finds the method of a module in the environment of its generic
+                let registration = method_class
+                    .filter(|_| var.is_from_other_module())
+                    .map(|class| format!("registerS3method('{0}', '{1}', {2}.{1}, envir = {3})\n",
+                            var.get_name(), class, new_name, var.1.get_value().replace("::", "$")))
+                    .unwrap_or_default();
+                (code + &registration, new_cont)
                 
             },
+            Lang::Destructure(pattern, _ttype, body, _) => {
+                let (body_str, new_cont) = body.to_r(cont);
+                let tmp = pattern.temporary_name();
+                let extractions = pattern.extractions(&tmp).iter()
+                    .map(|(var, access)| format!("{} <- {}", var.clone().to_r(cont), access))
+                    .collect::<Vec<_>>().join("\n");
+                (format!("{} <- {}\n{}\n", tmp, body_str, extractions), new_cont)
+            },
             Lang::Array(v, _h) => {
                 let vector = &self.linearize_array()
                     .iter().map(|lang| lang.to_r(&cont).0)
@@ -681,8 +782,19 @@ impl RTranslatable<(String, Context)> for Lang {
             },
             Lang::Empty(_) => 
                 ("NA".to_string(), cont.clone()),
-            Lang::ModuleDecl(name, _) => 
-                (format!("{} <- new.env()", name), cont.clone()),
+            Lang::ModuleDecl(name, _) => {
+                // the environment of a::b lives in the one of a, created if a is a directory
+                let segments = name.split("::").collect::<Vec<_>>();
+                let parents = (1..segments.len())
+                    .map(|i| segments[..i].join("$"))
+                    .map(|env| if env.contains('$') {
+                            format!("if (is.null({0})) {0} <- new.env()", env)
+                        } else {
+                            format!("if (!exists('{0}', inherits = FALSE)) {0} <- new.env()", env)
+                        });
+                let decl = format!("{} <- new.env()", segments.join("$"));
+                (parents.chain([decl]).collect::<Vec<_>>().join("\n"), cont.clone())
+            },
             Lang::Sequence(exps, _) => {
                 Translatable::from(cont.clone())
                     .join(exps, "\n").into()
@@ -691,8 +803,11 @@ impl RTranslatable<(String, Context)> for Lang {
                 Translatable::from(cont.clone())
                     .add("return ").to_r(exp).into()
             },
-            Lang::Lambda(bloc, _) 
-                => (format!("function(x) {{ {} }}", bloc.to_r(cont).0), cont.clone()),
+            Lang::Lambda(params, bloc, _) 
+                => (format!("function({}) {{ {} }}", 
+                        params.iter().map(|x| x.to_r()).collect::<Vec<_>>().join(", "),
+                        bloc.to_r(cont).0), cont.clone()),
+            Lang::Formula(text, _, _) => (text.clone(), cont.clone()),
             Lang::VecBloc(bloc, _) => (bloc.to_string(), cont.clone()),
             Lang::Library(name, _) => (format!("library({})", name), cont.clone()),
             Lang::Match(exp, var, branches, _) 
@@ -718,7 +833,7 @@ impl RTranslatable<(String, Context)> for Lang {
                 };
                 (format!("{} = {}", res, right.to_r(cont).0), cont.clone())
             }
-            Lang::Signature(_, _, _) => {
+            Lang::Signature(_, _, _, _) => {
                 ("".to_string(), cont.clone())
             }
             Lang::Alias(_, _, _, _) => ("".to_string(), cont.clone()),
@@ -732,3 +847,47 @@ impl RTranslatable<(String, Context)> for Lang {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_exp;
+
+    fn to_r_code(code: &str) -> String {
+        let exp = parse_exp(code.into()).unwrap().1;
+        let context = typing(&Context::default(), &exp).1;
+        exp.to_r(&context).0
+    }
+
+    #[test]
+    fn test_impl_block_methods() {
+        let code = to_r_code("type Show = interface { show: fn(a: Self): char };
+            type Point = {x: int, y: int};
+            impl Show for Point { let show <- fn(p: Point): char { \"point\" }; };
+            impl Show for int { let show <- fn(i: int): char { \"int\" }; };");
+        assert!(code.contains("show.Point <- (function(p) {"));
+        assert!(code.contains("show.integer <- (function(i) {"));
+    }
+
+    #[test]
+    fn test_list_indexing() {
+        let code = to_r_code("let xs <- [1, 2, 3];
+            let next <- fn(n: int): int { n };
+            xs[[next(1)]];");
+        assert!(code.contains("xs[[next(1L)]]"));
+    }
+
+    #[test]
+    fn test_na_body_is_defined() {
+        let exp = parse_exp("let f <- fn(): int? { NA };".into()).unwrap().1;
+        let body = match exp {
+            Lang::Sequence(exps, _) => match &exps[0] {
+                Lang::Let(_, _, body, _) => (**body).clone(),
+                _ => panic!("not a let")
+            },
+            _ => panic!("not a sequence")
+        };
+        assert!(!body.is_undefined());
+        assert!(body.to_r(&Context::default()).0.contains("NA"));
+    }
+}
@@ -41,6 +41,19 @@ mod config;
 mod graph;
 mod type_category;
 mod typer;
+mod pattern;
+mod parameters;
+mod source_map;
+mod inference;
+mod narrowing;
+mod nullable;
+mod data_frame;
+mod tidy;
+mod factor;
+mod matrix;
+mod index_solver;
+mod condition;
+mod row;
 
 use crate::help_message::TypeError;
 use parser::parse;
@@ -0,0 +1,54 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::help_data::HelpData;
+use crate::function_type::FunctionType;
+use crate::parse;
+use crate::TypeError;
+use crate::help_message::ErrorMsg;
+use crate::type_checker::typing;
+use std::sync::OnceLock;
+
+// A matrix is an array of arrays: [#M, [#N, num]] has #M rows and #N columns.
+// The dimensions are unified across the arguments of R's linear algebra
+// functions, whose shapes are the signatures of configs/r/lin_alg.ty, so a
+// product of matrices that don't fit is a type error.
+const LIN_ALG: &str = include_str!("../configs/r/lin_alg.ty");
+
+fn is_matrix(typ: &Type) -> bool {
+    matches!(typ, Type::Array(_, elem_ty, _) if matches!(**elem_ty, Type::Array(_, _, _)))
+}
+
+// the signatures of lin_alg.ty, parsed once
+fn shapes() -> &'static [(String, FunctionType)] {
+    static SHAPES: OnceLock<Vec<(String, FunctionType)>> = OnceLock::new();
+    SHAPES.get_or_init(|| parse(LIN_ALG.into()).map(|(_, adt)| adt.iter()
+            .filter_map(|line| match line {
+                Lang::Signature(var, typ, _, _) => typ.to_function_type().map(|shape| (var.get_name(), shape)),
+                _ => None
+            }).collect())
+        .unwrap_or_default())
+}
+
+fn shape(fn_name: &str) -> Option<FunctionType> {
+    shapes().iter()
+        .find(|(name, _)| name == fn_name)
+        .map(|(_, shape)| shape.clone())
+}
+
+/// Type of the matrix functions (%*%, t, cbind, rbind, solve), None when they aren't given matrices
+pub fn matrix_call(context: &Context, fn_name: &str, values: &[Lang], h: &HelpData) -> Option<Type> {
+    let shape = shape(fn_name)?;
+    let param_types = shape.get_param_types();
+    let arg_types = values.iter()
+        .map(|value| typing(context, value).0)
+        .collect::<Vec<_>>();
+    let all_matrices = values.len() == param_types.len()
+        && arg_types.iter().all(|typ| is_matrix(&typ.reduce(context)));
+    all_matrices.then(|| {
+        context.get_unification_map(values, &arg_types, &param_types)
+            .unwrap_or_else(|| panic!("{}", TypeError::Param(param_types[0].clone(), arg_types[0].clone()).display()))
+            .apply_unification_type(context, &shape.get_ret_type()).0
+            .set_help_data(h.clone())
+    })
+}
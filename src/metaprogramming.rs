@@ -1,25 +1,175 @@
-use crate::my_io::read_file_from_name;
+use crate::my_io::read_file;
 use crate::my_io::get_os_file;
 use crate::adt::Adt;
 use crate::Lang;
 use crate::parse;
 use nom_locate::LocatedSpan;
 use crate::help_data::HelpData;
+use crate::var::Var;
+use crate::Type;
+use crate::builder;
+use crate::Context;
+use crate::TypeError;
+use crate::help_message::ErrorMsg;
+use std::path::PathBuf;
+use std::fs;
 
 
-fn import_file_module_code(line: &Lang) -> Lang {
+// mod a::b; lives in a/b.ty next to the importing file
+fn module_file(name: &str, h: &HelpData) -> PathBuf {
+    PathBuf::from(h.get_file_name())
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_default()
+        .join(format!("{}.ty", name.replace("::", "/")))
+}
+
+fn same_file(file1: &PathBuf, file2: &PathBuf) -> bool {
+    match (fs::canonicalize(file1), fs::canonicalize(file2)) {
+        (Ok(f1), Ok(f2)) => f1 == f2,
+        _ => file1 == file2
+    }
+}
+
+// chain: files being imported, from the main file to the current one
+fn import_file_module_code(line: &Lang, chain: &[PathBuf]) -> Lang {
     match line {
-        Lang::ModImp(name, _h) => {
-            let file = get_os_file(&format!("{}.ty", name));
-            let new_adt = metaprogrammation(parse(LocatedSpan::new_extra(&read_file_from_name(&name), file)).unwrap().1);
-            Lang::Module(name.to_string(), new_adt.0, HelpData::default())
+        Lang::ModImp(name, h) => {
+            let chain = if chain.is_empty() {
+                vec![PathBuf::from(h.get_file_name())]
+            } else { chain.to_vec() };
+            let file = module_file(name, h);
+            if chain.iter().any(|f| same_file(f, &file)) {
+                let cycle = chain.iter().chain([&file])
+                    .map(|f| f.display().to_string())
+                    .collect::<Vec<_>>();
+                panic!("{}", TypeError::ImportCycle(cycle, h.clone()).display())
+            }
+            let content = read_file(&file);
+            let adt = parse(LocatedSpan::new_extra(&content, get_os_file(&file.display().to_string()))).unwrap().1;
+            let new_chain = chain.into_iter().chain([file]).collect::<Vec<_>>();
+            let new_adt = expand_modules(adt, &new_chain);
+            Lang::Module(name.clone(), new_adt.0, h.clone())
         }
         n => n.clone()
     }
 }
 
-fn import_file_modules_code(adt: Adt) -> Adt {
-    adt.iter().map(import_file_module_code).collect::<Vec<_>>().into()
+fn import_file_modules_code(adt: Adt, chain: &[PathBuf]) -> Adt {
+    adt.iter().map(|line| import_file_module_code(line, chain)).collect::<Vec<_>>().into()
+}
+
+// modules are keyed by their full path: a::b is the module of a/b.ty or the module b declared in a
+fn find_module<'a>(lines: &'a [Lang], path: &str) -> Option<&'a Vec<Lang>> {
+    lines.iter().find_map(|line| match line {
+        Lang::Module(name, body, _) if name == path => Some(body),
+        Lang::Module(name, body, _) => path.strip_prefix(&format!("{}::", name))
+            .and_then(|rest| find_module(body, rest)),
+        _ => None
+    })
+}
+
+fn item_var(item: &Lang) -> Option<&Var> {
+    match item {
+        Lang::Let(var, _, _, _) | Lang::Alias(var, _, _, _) => Some(var),
+        _ => None
+    }
+}
+
+fn alias_shortcut(module: &str, name: &str, params: &[Type], is_pub: bool, h: &HelpData) -> Lang {
+    Lang::Alias(
+        Var::from_name(name)
+            .set_type(Type::Params(params.to_vec(), h.clone()), &Context::default())
+            .set_permission(is_pub)
+            .set_help_data(h.clone()),
+        params.to_vec(),
+        Type::Alias(name.to_string(), params.to_vec(), module.into(), false, h.clone()),
+        h.clone())
+}
+
+fn let_shortcut(module: &str, name: &str, is_pub: bool, h: &HelpData) -> Lang {
+    let target = Var::from_name(name)
+        .set_path(module.into())
+        .set_help_data(h.clone());
+    Lang::Let(
+        Var::from_name(name).set_permission(is_pub).set_help_data(h.clone()),
+        builder::empty_type(),
+        Box::new(target.into()),
+        h.clone())
+}
+
+// local binding to the item of a module: an Alias for a type, a Let for a value
+fn use_shortcut(module: &str, name: &str, item: Option<&Lang>, is_pub: bool, h: &HelpData) -> Lang {
+    match item {
+        Some(Lang::Alias(_, params, _, _)) => alias_shortcut(module, name, params, is_pub, h),
+        None if name.starts_with(char::is_uppercase) => alias_shortcut(module, name, &[], is_pub, h),
+        _ => let_shortcut(module, name, is_pub, h)
+    }
+}
+
+// items of a module, with the bindings of its `pub use` re-exports
+fn module_items(body: &[Lang], scope: &[Lang]) -> Vec<Lang> {
+    body.iter().flat_map(|line| match line {
+        Lang::Use(_, _, true, _) => resolve_use(line, scope),
+        _ => vec![line.clone()]
+    }).collect()
+}
+
+// replace each `use` by the bindings it introduces
+fn resolve_use(line: &Lang, scope: &[Lang]) -> Vec<Lang> {
+    match line {
+        // use f; and use T; name an item already in scope
+        Lang::Use(path, _, _, _) if path.is_empty() => vec![],
+        Lang::Use(path, names, is_pub, h) => {
+            let module = path.clone();
+            match (find_module(scope, &module).map(|body| module_items(body, scope)), names) {
+                (Some(items), None) => items.iter()
+                    .filter(|item| item_var(item).is_some_and(Var::is_public))
+                    .map(|item| use_shortcut(&module, &item_var(item).unwrap().get_name(), Some(item), *is_pub, h))
+                    .collect(),
+                (Some(items), Some(names)) => names.iter().map(|name| {
+                    let item = items.iter()
+                        .find(|item| item_var(item).is_some_and(|var| var.get_name() == *name))
+                        .unwrap_or_else(|| panic!("{}",
+                            TypeError::UnknownImport(name.clone(), path.clone(), h.clone()).display()));
+                    use_shortcut(&module, name, Some(item), *is_pub, h)
+                }).collect(),
+                (None, names) => {
+                    let name = names.iter().flatten().next().cloned().unwrap_or("*".to_string());
+                    panic!("{}", TypeError::UnknownImport(name, path.clone(), h.clone()).display())
+                }
+            }
+        },
+        Lang::Module(name, body, h) => {
+            let new_body = body.iter().flat_map(|l| resolve_use(l, scope)).collect();
+            vec![Lang::Module(name.clone(), new_body, h.clone())]
+        },
+        lang => vec![lang.clone()]
+    }
+}
+
+fn resolve_uses(adt: Adt) -> Adt {
+    adt.iter().flat_map(|line| resolve_use(line, &adt.0)).collect::<Vec<_>>().into()
+}
+
+// the signature of a module's function refers to the module's types
+fn function_path(module_name: &str, lang: &Lang) -> Lang {
+    match lang {
+        Lang::Function(k, a, d, r, b, h) => {
+            let arg_typ = a.iter()
+                .map(|a_t| a_t.to_owned().set_type(a_t.get_type().add_path(module_name.into())))
+                .collect::<Vec<_>>();
+            Lang::Function(k.to_owned(), arg_typ.to_owned(), d.to_owned(),
+                r.to_owned().add_path(module_name.into()), b.to_owned(), h.to_owned())
+        },
+        lang => lang.clone()
+    }
+}
+
+// the type of a function's name (its first parameter) too
+fn var_path(module_name: &str, var: &Var) -> Var {
+    let Var(name, path, perm, muta, opa, typ, h) = var.clone().add_path(module_name.into());
+    Var(name, path, perm, muta, opa, typ.add_path(module_name.into()), h)
 }
 
 fn accessibility_change(module_name: &str, adt: Adt) -> Vec<Lang> {
@@ -27,41 +177,44 @@ fn accessibility_change(module_name: &str, adt: Adt) -> Vec<Lang> {
         match line {
             Lang::Let(var, typ, body, h) 
                 => Lang::Let(
-                    var.clone().add_path(module_name.into()),
-                    typ.to_owned().add_path(module_name.into()), body.clone(), h.clone()),
+                    var_path(module_name, var),
+                    typ.to_owned().add_path(module_name.into()),
+                    Box::new(function_path(module_name, body)), h.clone()),
             Lang::Alias(var, params, typ, h) 
                 => Lang::Alias(
                     var.clone().add_path(module_name.into()),
                     params.clone(), typ.to_owned().add_path(module_name.into()), h.clone()),
-            Lang::Function(k, a, r, b, h) => {
-                let arg_typ = a.iter()
-                    .map(|a_t| a_t.to_owned().set_type(a_t.get_type().add_path(module_name.into())))
-                    .collect::<Vec<_>>();
-                Lang::Function(k.to_owned(), arg_typ.to_owned(),
-                    r.to_owned().add_path(module_name.into()), b.to_owned(), h.to_owned())
-            },
+            Lang::Function(_, _, _, _, _, _) => function_path(module_name, line),
             _ => Lang::Empty(line.clone().into())
         }
     }).collect::<Vec<_>>()
 }
 
-fn unnest_module(line: &Lang) -> Vec<Lang> {
+// a module declared in the module a is the module a::b
+fn unnest_module(line: &Lang, parent: &str) -> Vec<Lang> {
     match line {
-        Lang::Module(name, body, h) 
-            => {
-                let new_adt = unnest_modules(body.clone().into());
-                let mut lines = accessibility_change(name, new_adt);
-                lines.insert(0, Lang::ModuleDecl(name.to_string(), h.clone()));
-                lines
-            },
+        Lang::Module(name, body, h) => {
+            let path = if parent.is_empty() { name.clone() } else { format!("{}::{}", parent, name) };
+            let (modules, items): (Vec<_>, Vec<_>) = body.iter()
+                .cloned()
+                .partition(|l| matches!(l, Lang::Module(..)));
+            [Lang::ModuleDecl(path.clone(), h.clone())].into_iter()
+                .chain(modules.iter().flat_map(|module| unnest_module(module, &path)))
+                .chain(accessibility_change(&path, items.into()))
+                .collect()
+        },
         lang => vec![lang.clone()]
     }
 }
 
 fn unnest_modules(adt: Adt) -> Adt {
-    adt.iter().flat_map(unnest_module).collect::<Vec<_>>().into()
+    adt.iter().flat_map(|line| unnest_module(line, "")).collect::<Vec<_>>().into()
+}
+
+fn expand_modules(adt: Adt, chain: &[PathBuf]) -> Adt {
+    unnest_modules(resolve_uses(import_file_modules_code(adt, chain)))
 }
 
 pub fn metaprogrammation(adt: Adt) -> Adt {
-    unnest_modules(import_file_modules_code(adt))
+    expand_modules(adt, &[])
 }
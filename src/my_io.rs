@@ -2,6 +2,7 @@
 use std::process::Command;
 use std::fs;
 use std::path::PathBuf;
+use crate::source_map::SourceMap;
 
 
 pub fn get_os_file(file: &str) -> String {
@@ -47,15 +48,29 @@ pub fn execute_r() -> () {
     }
 }
 
+// keep the sources so an error gives the line of the R file ("main.R#12"),
+// the file comes from the arguments so its name is never read as R code
+const R_RUNNER: &str = "options(show.error.locations = TRUE); \
+    invisible(source(commandArgs(trailingOnly = TRUE)[1], keep.source = TRUE, print.eval = TRUE))";
+
 pub fn execute_r_with_path(execution_path: &PathBuf, file_name: &str) -> () {
+    let source_map = SourceMap::read(&execution_path.join(format!("{}.map", file_name)))
+        .unwrap_or_default();
+    let to_typr = |output: &[u8]| {
+        let text = String::from_utf8_lossy(output);
+        if source_map.mappings.is_empty() { text.to_string() } else { source_map.rewrite(&text) }
+    };
     match Command::new("Rscript")
         .current_dir(execution_path)
+        .arg("-e")
+        .arg(R_RUNNER)
+        .arg("--args")
         .arg(get_os_file(file_name))
         .output()
     {
         Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = to_typr(&output.stdout);
+            let stderr = to_typr(&output.stderr);
             
             if output.status.success() {
                 println!("Execution: \n{}", stdout);
@@ -0,0 +1,95 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::var::Var;
+use crate::help_data::HelpData;
+use crate::type_checker::typing;
+use crate::type_checker::match_cases;
+
+// if (is_int(x)) { ... } else { ... }: what the condition proves about
+// a variable refines its type inside each branch. The R class tests
+// (is.character, is.null, ...) are type guards declared in std.ty.
+
+// @is_int: (x: any) -> x is int;
+// A guard tests one value: is.na of a vector is a vector of booleans
+// (the overload of std.ty), which doesn't narrow it.
+fn guard_test(context: &Context, fn_name: &str, values: &[Lang]) -> Option<(Lang, Vec<Type>)> {
+    let (param, typ) = context.variables()
+        .filter(|(var, _)| var.get_name() == fn_name)
+        .filter_map(|(_, typ)| typ.to_function_type())
+        .find_map(|fn_typ| match fn_typ.get_ret_type() {
+            Type::Guard(param, typ, _) => Some((param, typ)),
+            _ => None
+        })?;
+    let position = context.get_parameters(fn_name)
+        .and_then(|params| params.0.iter().position(|name| *name == param))
+        .unwrap_or(0);
+    values.get(position)
+        .filter(|value| !matches!(typing(context, value).0.reduce(context), Type::Array(_, _, _)))
+        .map(|value| (value.clone(), match_cases(context, &typ.reduce(context))))
+}
+
+fn fits(context: &Context, case: &Type, proven: &Type) -> bool {
+    match (case, proven) {
+        (Type::Empty(_), Type::Empty(_)) => true,
+        (Type::Empty(_), _) | (_, Type::Empty(_)) => false,
+        // a tag test doesn't look at the content: .Some(int) is a .Some(T)
+        (Type::Tag(name1, _, _), Type::Tag(name2, _, _)) => name1 == name2,
+        _ => case.is_subtype(proven, context)
+    }
+}
+
+fn to_type(types: Vec<Type>, h: HelpData) -> Option<Type> {
+    match types.len() {
+        0 => None,
+        1 => types.into_iter().next(),
+        _ => Some(Type::Union(types.into_iter().collect(), h))
+    }
+}
+
+// the type of the variable when the test is true and when it's false
+fn refine(context: &Context, value: &Lang, proven: &[Type]) -> (Option<Type>, Option<Type>) {
+    let typ = typing(context, value).0.reduce(context);
+    match typ {
+        Type::Any(h) => (to_type(proven.to_vec(), h), None),
+        typ => {
+            let (matching, others): (Vec<_>, Vec<_>) = match_cases(context, &typ).into_iter()
+                .partition(|case| proven.iter().any(|p| fits(context, case, p)));
+            (to_type(matching, typ.get_help_data()), to_type(others, typ.get_help_data()))
+        }
+    }
+}
+
+fn push_refined(context: &Context, value: &Lang, typ: Option<Type>) -> Context {
+    match (Var::try_from(value.clone()), typ) {
+        (Ok(var), Some(typ)) => context.clone().push_var_type(var, typ, context),
+        _ => context.clone()
+    }
+}
+
+/// Contexts of the true and the false branch of a condition
+pub fn narrow(context: &Context, cond: &Lang) -> (Context, Context) {
+    match cond {
+        Lang::FunctionApp(fn_name, values, _, _) => {
+            let name = Var::try_from(fn_name.clone())
+                .map(|var| var.get_name())
+                .unwrap_or_default();
+            match guard_test(context, &name, values) {
+                Some((value @ Lang::Variable(..), proven)) => {
+                    let (if_true, if_false) = refine(context, &value, &proven);
+                    (push_refined(context, &value, if_true), push_refined(context, &value, if_false))
+                },
+                _ => (context.clone(), context.clone())
+            }
+        },
+        Lang::And(cond1, cond2, _) => {
+            let (if_true, _) = narrow(context, cond1);
+            (narrow(&if_true, cond2).0, context.clone())
+        },
+        Lang::Or(cond1, cond2, _) => {
+            let (_, if_false) = narrow(context, cond1);
+            (context.clone(), narrow(&if_false, cond2).1)
+        },
+        _ => (context.clone(), context.clone())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::Type;
+
+// R functions giving NA as soon as one of their arguments is NA
+const NA_PROPAGATING: [&str; 13] = [
+    "add", "minus", "mul", "div", "add2", "minus2", "mul2", "div2",
+    "abs", "sqrt", "exp", "log", "round"
+];
+
+pub fn propagates_na(fn_name: &str) -> bool {
+    NA_PROPAGATING.contains(&fn_name)
+}
+
+pub fn is_nullable(typ: &Type) -> bool {
+    matches!(typ, Type::Nullable(_, _))
+}
+
+/// T? gives T
+pub fn non_null(typ: Type) -> Type {
+    match typ {
+        Type::Nullable(typ, _) => *typ,
+        typ => typ
+    }
+}
+
+/// T gives T?
+pub fn nullable(typ: Type) -> Type {
+    match typ {
+        Type::Nullable(_, _) | Type::Empty(_) | Type::Any(_) => typ,
+        typ => {
+            let h = typ.get_help_data();
+            Type::Nullable(Box::new(typ), h)
+        }
+    }
+}
@@ -50,6 +50,12 @@ impl Op {
     pub fn to_type(&self) -> Option<Type> {
         match self {
             Op::In(h) => Some(Type::In(h.clone())),
+            Op::LesserThan(h) => Some(Type::Comparison("<".to_string(), h.clone())),
+            Op::GreaterThan(h) => Some(Type::Comparison(">".to_string(), h.clone())),
+            Op::LesserOrEqual(h) => Some(Type::Comparison("<=".to_string(), h.clone())),
+            Op::GreaterOrEqual(h) => Some(Type::Comparison(">=".to_string(), h.clone())),
+            Op::Eq(h) => Some(Type::Comparison("==".to_string(), h.clone())),
+            Op::NotEq(h) => Some(Type::Comparison("!=".to_string(), h.clone())),
             _ => None
         }
     }
@@ -0,0 +1,70 @@
+use serde::Serialize;
+use crate::Lang;
+use crate::argument_type::ArgumentType;
+use crate::argument_value::ArgumentValue;
+use crate::help_data::HelpData;
+use crate::TypeError;
+use crate::help_message::ErrorMsg;
+
+// names of the parameters of a function (in order) and their default values
+#[derive(Debug, Clone, PartialEq, Serialize, Default)]
+pub struct Parameters(pub Vec<String>, pub Vec<ArgumentValue>);
+
+impl Parameters {
+    pub fn new(args: &[ArgumentType], defaults: &[ArgumentValue]) -> Parameters {
+        Parameters(
+            args.iter().map(ArgumentType::get_argument_str).collect(),
+            defaults.to_vec())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get_defaults(&self) -> Vec<ArgumentValue> {
+        self.1.clone()
+    }
+
+    pub fn get_default(&self, name: &str) -> Option<Lang> {
+        self.1.iter()
+            .find(|arg_val| arg_val.get_argument() == name)
+            .map(ArgumentValue::get_value)
+    }
+
+    /// Put the values of a call in the order of the parameters.
+    /// Omitted parameters take their default value, extra ones are an error.
+    pub fn arrange(&self, values: &[Lang], h: &HelpData) -> Vec<Lang> {
+        let named = values.iter()
+            .filter_map(named_argument)
+            .collect::<Vec<_>>();
+        if let Some((name, value)) = named.iter().find(|(name, _)| !self.0.contains(name)) {
+            panic!("{}", TypeError::UnknownArgument(name.clone(), value.get_help_data()).display())
+        }
+        let mut positional = values.iter()
+            .filter(|value| named_argument(value).is_none());
+        let arranged = self.0.iter()
+            .map(|name| {
+                named.iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, value)| value.clone())
+                    .or_else(|| positional.next().cloned())
+                    .or_else(|| self.get_default(name))
+                    .unwrap_or_else(|| panic!("{}", TypeError::MissingArgument(name.clone(), h.clone()).display()))
+            }).collect::<Vec<_>>();
+        if let Some(extra) = positional.next() {
+            panic!("{}", TypeError::TooManyArguments(self.0.len(), extra.get_help_data()).display())
+        }
+        arranged
+    }
+}
+
+// f(x, scale = 2.0)
+pub fn named_argument(value: &Lang) -> Option<(String, Lang)> {
+    match value {
+        Lang::Eq2(value, name, _) => match &**name {
+            Lang::Variable(n, _, _, _, _, _) => Some((n.clone(), (**value).clone())),
+            _ => None
+        },
+        _ => None
+    }
+}
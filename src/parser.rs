@@ -2,14 +2,18 @@ use nom::IResult;
 use crate::language::Lang;
 use crate::elements::parse_elements;
 use nom::character::complete::multispace0;
+use nom::character::complete::multispace1;
 use nom::sequence::terminated;
 use nom::bytes::complete::tag;
 use crate::elements::variable;
 use crate::types::type_alias;
 use crate::types::ltype;
+use crate::types::utype;
+use crate::types::guard_type;
 use crate::r#type::Type;
 use crate::var::Var;
 use nom::combinator::opt;
+use nom::combinator::recognize;
 use nom::sequence::delimited;
 use crate::elements::tag_exp;
 use nom::character::complete::not_line_ending;
@@ -27,6 +31,15 @@ use crate::elements::single_element;
 use crate::elements::scope;
 use crate::operators::custom_op;
 use crate::Context;
+use crate::pattern::Pattern;
+use nom::multi::many1;
+use nom::multi::separated_list1;
+use crate::types::pascal_case;
+use crate::elements::parameter;
+use crate::elements::extract_generics;
+use crate::parameters::Parameters;
+use crate::argument_type::ArgumentType;
+use crate::builder;
 
 type Span<'a> = LocatedSpan<&'a str, String>;
 
@@ -74,11 +87,11 @@ fn base_let_exp(s: Span) -> IResult<Span, Vec<Lang>> {
             single_parse,
           ).parse(s);
     match res {
-        Ok((s, (_let, (pat_var, None), typ, _eq, Lang::Function(ki, params, ty, body, h)))) 
+        Ok((s, (_let, (pat_var, None), typ, _eq, Lang::Function(ki, params, defaults, ty, body, h)))) 
             if params.len() > 0 => {
                 let newvar = Var::from_language(pat_var[0].clone()).unwrap().set_type(params[0].1.clone(), &Context::default()).set_permission(false);
                 Ok((s, vec![Lang::Let(newvar, typ.unwrap_or(Type::Empty(HelpData::default())),
-                Box::new(Lang::Function(ki, params, ty, body, h)), _let.into())]))
+                Box::new(Lang::Function(ki, params, defaults, ty, body, h)), _let.into())]))
             },
         Ok((s, (_let, (pat_var, None), typ, _eq, body))) => {
                 Ok((s, 
@@ -118,11 +131,11 @@ fn base_let_mut_exp(s: Span) -> IResult<Span, Vec<Lang>> {
             single_parse,
           ).parse(s);
     match res {
-        Ok((s, ((pat_var, None), typ, _eq, Lang::Function(ki, params, ty, body, h)))) 
+        Ok((s, ((pat_var, None), typ, _eq, Lang::Function(ki, params, defaults, ty, body, h)))) 
             if params.len() > 0 => {
                 let newvar = Var::from_language(pat_var[0].clone()).unwrap().set_type(params[0].1.clone(), &Context::default()).set_permission(false);
                 Ok((s, vec![Lang::Let(newvar, typ.unwrap_or(Type::Empty(HelpData::default())),
-                Box::new(Lang::Function(ki, params, ty, body, h)), pat_var.into())]))
+                Box::new(Lang::Function(ki, params, defaults, ty, body, h)), pat_var.into())]))
             },
         Ok((s, ((pat_var, None), typ, _eq, body))) => {
                 Ok((s, 
@@ -177,10 +190,78 @@ fn let_mut_exp(s: Span) -> IResult<Span, Vec<Lang>> {
 }
 
 
+fn var_pattern(s: Span) -> IResult<Span, Pattern> {
+    let res = terminated(variable_exp, multispace0).parse(s);
+    match res {
+        Ok((s, (name, h))) => Ok((s, Pattern::Var(Var::from_name(&name).set_help_data(h)))),
+        Err(r) => Err(r)
+    }
+}
+
+fn tuple_pattern(s: Span) -> IResult<Span, Pattern> {
+    let res = (
+        terminated(tag("("), multispace0),
+        many1(terminated(destructure_pattern, opt(terminated(tag(","), multispace0)))),
+        terminated(tag(")"), multispace0)
+              ).parse(s);
+    match res {
+        Ok((s, (op, pats, _cl))) => Ok((s, Pattern::Tuple(pats, op.into()))),
+        Err(r) => Err(r)
+    }
+}
+
+fn field_pattern(s: Span) -> IResult<Span, (String, Pattern)> {
+    let res = (
+        terminated(variable_exp, multispace0),
+        opt(preceded(terminated(tag(":"), multispace0), destructure_pattern))
+              ).parse(s);
+    match res {
+        Ok((s, ((name, _h), Some(pat)))) => Ok((s, (name, pat))),
+        Ok((s, ((name, h), None))) 
+            => Ok((s, (name.clone(), Pattern::Var(Var::from_name(&name).set_help_data(h))))),
+        Err(r) => Err(r)
+    }
+}
+
+fn record_pattern(s: Span) -> IResult<Span, Pattern> {
+    let res = (
+        terminated(tag("{"), multispace0),
+        many1(terminated(field_pattern, opt(terminated(tag(","), multispace0)))),
+        terminated(tag("}"), multispace0)
+              ).parse(s);
+    match res {
+        Ok((s, (op, fields, _cl))) => Ok((s, Pattern::Record(fields, op.into()))),
+        Err(r) => Err(r)
+    }
+}
+
+fn destructure_pattern(s: Span) -> IResult<Span, Pattern> {
+    alt((tuple_pattern, record_pattern, var_pattern)).parse(s)
+}
+
+// let {x, y} <- point; let (a, b) <- pair;
+fn destructuring_let_exp(s: Span) -> IResult<Span, Vec<Lang>> {
+    let res = (
+            terminated(tag("let"), multispace0),
+            alt((tuple_pattern, record_pattern)),
+            opt(preceded(terminated(tag(":"), multispace0), ltype)),
+            equality_operator,
+            single_parse,
+          ).parse(s);
+    match res {
+        Ok((s, (_let, pattern, typ, _eq, body))) 
+            => Ok((s, vec![Lang::Destructure(
+                        pattern.set_permission(false),
+                        typ.unwrap_or(Type::Empty(HelpData::default())),
+                        Box::new(body), _let.into())])),
+        Err(r) => Err(r)
+    }
+}
+
 fn let_exp(s: Span) -> IResult<Span, Vec<Lang>> {
     let res = (
         opt(terminated(tag("pub"), multispace0)),
-        base_let_exp
+        alt((destructuring_let_exp, base_let_exp))
                     ).parse(s);
     match res {
         Ok((s, (None, le))) => Ok((s, le)),
@@ -191,6 +272,10 @@ fn let_exp(s: Span) -> IResult<Span, Vec<Lang>> {
                         => Lang::Let(var.clone().set_permission(true),
                                     typ.clone(),
                                     body.clone(), h.clone()),
+                    Lang::Destructure(pattern, typ, body, h) 
+                        => Lang::Destructure(pattern.clone().set_permission(true),
+                                    typ.clone(),
+                                    body.clone(), h.clone()),
                     lan => lan.clone()
                 }
             }).collect();
@@ -200,23 +285,24 @@ fn let_exp(s: Span) -> IResult<Span, Vec<Lang>> {
     }
 }
 
+// let mut x <- 1;  (or mut x <- 1;)
 fn base_mut_exp(s: Span) -> IResult<Span, Lang> {
     let res = (
-            terminated(tag("mut"), multispace0),
+            terminated(alt((recognize((tag("let"), multispace1, tag("mut"))), tag("mut"))), multispace1),
             pattern_var,
             opt(preceded(terminated(tag(":"), multispace0), ltype)),
             equality_operator,
             single_parse,
           ).parse(s);
     match res {
-        Ok((s, (_met, (var, None), typ, _eq, Lang::Function(ki, params, ty, body, h)))) 
+        Ok((s, (_met, (var, None), typ, _eq, Lang::Function(ki, params, defaults, ty, body, h)))) 
             if params.len() > 0 => {
                 let newvar = Var::from_language(var[0].clone())
                     .unwrap()
                     .set_type(params[0].1.clone(), &Context::default())
                     .set_mutability(true);
                 Ok((s, Lang::Let(newvar, typ.unwrap_or(Type::Empty(HelpData::default())),
-                Box::new(Lang::Function(ki, params, ty, body, h.clone())), h)))
+                Box::new(Lang::Function(ki, params, defaults, ty, body, h.clone())), h)))
             },
         Ok((s, (_let, (var, _), typ, _eq, body))) => {
             Ok((s, Lang::Let(
@@ -343,6 +429,24 @@ pub fn module(s: Span) -> IResult<Span, Vec<Lang>> {
     }
 }
 
+// impl Addable for int { let add <- fn(a: int, b: int): int { a }; };
+fn impl_exp(s: Span) -> IResult<Span, Vec<Lang>> {
+    let res = (terminated(tag("impl"), multispace1),
+        terminated(type_alias, multispace0),
+        terminated(tag("for"), multispace1),
+        terminated(utype, multispace0),
+        terminated(tag("{"), multispace0),
+        many0(let_exp),
+        terminated(tag("}"), multispace0),
+        terminated(tag(";"), multispace0)
+          ).parse(s);
+    match res {
+        Ok((s, (imp, interface, _for, typ, _op, methods, _cl, _sc))) =>
+            Ok((s, vec![Lang::Impl(interface, typ, methods.concat(), imp.into())])),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn return_exp(s: Span) -> IResult<Span, Lang> {
     let res = terminated(delimited(tag("return "), parse_elements, tag(";")), multispace0).parse(s);
     match res {
@@ -352,9 +456,22 @@ pub fn return_exp(s: Span) -> IResult<Span, Lang> {
     }
 }
 
+// p$x: a field of a record variable
+fn field_target(s: Span) -> IResult<Span, Lang> {
+    let res = (variable, many1(preceded(tag("$"), variable))).parse(s);
+    match res {
+        Ok((s, (record, fields))) => Ok((s, fields.into_iter()
+                .fold(record, |record, field| {
+                    let h = field.get_help_data();
+                    Lang::Chain(Box::new(field), Box::new(record), h)
+                }))),
+        Err(r) => Err(r)
+    }
+}
+
 fn assign(s: Span) -> IResult<Span, Vec<Lang>> {
     let res = (
-            variable,
+            alt((field_target, variable)),
             alt((
                 terminated(tag("="), multispace0),
                 terminated(tag("<-"), multispace0))),
@@ -401,40 +518,61 @@ pub fn simple_exp(s: Span) -> IResult<Span, Vec<Lang>> {
     }
 }
 
+// a::b
+fn module_segments(s: Span) -> IResult<Span, Vec<(String, HelpData)>> {
+    separated_list1(tag("::"), variable_exp).parse(s)
+}
+
 fn mod_imp(s: Span) -> IResult<Span, Vec<Lang>> {
     let res = (terminated(tag("mod"), multispace0),
-            terminated(variable_exp, multispace0),
+            terminated(module_segments, multispace0),
             terminated(tag(";"), multispace0)).parse(s);
     match res {
-        Ok((s, (_mod, (name, _), _sc))) 
-            => Ok((s, vec![Lang::ModImp(name.to_string(), _mod.into())])),
+        Ok((s, (_mod, segments, _sc))) => {
+            let name = segments.iter()
+                .map(|(seg, _)| seg.clone())
+                .collect::<Vec<_>>()
+                .join("::");
+            Ok((s, vec![Lang::ModImp(name, _mod.into())]))
+        },
         Err(r) => Err(r)
     }
 }
 
-fn import_var(s: Span) -> IResult<Span, Vec<Lang>> {
-    let res = (terminated(tag("use"), multispace0),
-                variable,
-                terminated(tag(";"), multispace0)).parse(s);
+fn imported_name(s: Span) -> IResult<Span, String> {
+    let res = terminated(alt((variable_exp, pascal_case)), multispace0).parse(s);
     match res {
-        Ok((s, (_use, Lang::Variable(name, path, perm, mutop, typ, h), _sc))) => {
-            let var1 =  Lang::Variable(name.clone(), path.clone(), perm.clone(), mutop.clone(), typ.clone(), h.clone());
-            let var2 =  Lang::Variable(name.clone(), "".into(), perm.clone(), mutop.clone(), typ.clone(), h.clone());
-            let shortcut = Lang::Let(Var::from_language(var2).unwrap(), Type::Any(HelpData::default()), Box::new(var1), h.clone());
-            Ok((s, vec![shortcut]))
-        }
-        Err(r) => Err(r),
-        _ => todo!()
+        Ok((s, (name, _h))) => Ok((s, name)),
+        Err(r) => Err(r)
     }
 }
 
-fn import_type(s: Span) -> IResult<Span, Vec<Lang>> {
-    let res = (terminated(tag("use"), multispace0),
-            type_alias,
-            terminated(tag(";"), multispace0)).parse(s);
+// f  or  {f, T}  or  *
+fn imported_names(s: Span) -> IResult<Span, Option<Vec<String>>> {
+    alt((
+            terminated(tag("*"), multispace0).map(|_| None),
+            delimited(
+                terminated(tag("{"), multispace0),
+                separated_list1(terminated(tag(","), multispace0), imported_name),
+                terminated(tag("}"), multispace0)).map(Some),
+            imported_name.map(|name| Some(vec![name])))).parse(s)
+}
 
+// use f;  use a::b::{f, T};  pub use a::b::*;
+fn use_exp(s: Span) -> IResult<Span, Vec<Lang>> {
+    let res = (opt(terminated(tag("pub"), multispace0)),
+            terminated(tag("use"), multispace0),
+            many0(terminated(variable_exp, tag("::"))),
+            imported_names,
+            terminated(tag(";"), multispace0)).parse(s);
     match res {
-        Ok((s, (_use, alias, _sc))) => Ok((s, vec![Lang::Import(alias, _use.into())])),
+        Ok((s, (pu, _use, segments, names, _sc))) => {
+            let module = segments.iter()
+                .map(|(seg, _)| seg.clone())
+                .collect::<Vec<_>>()
+                .join("::");
+            Ok((s, vec![Lang::Use(module, names, pu.is_some(), _use.into())]))
+        },
         Err(r) => Err(r)
     }
 }
@@ -472,17 +610,47 @@ fn custom_operators(s: Span) -> IResult<Span, (String, HelpData)> {
     }
 }
 
+// (file: char, header: bool = true) -> T
+// the return type can be omitted for R functions
+fn named_parameters_type(s: Span) -> IResult<Span, (Type, Parameters)> {
+    let res = (
+            terminated(tag("("), multispace0),
+            many1(parameter),
+            terminated(tag(")"), multispace0),
+            opt(preceded(terminated(tag("->"), multispace0), alt((guard_type, ltype))))
+          ).parse(s);
+    match res {
+        Ok((s, (start, params, _, ret))) => {
+            let (args, defaults): (Vec<_>, Vec<_>) = params.into_iter().unzip();
+            let defaults = defaults.into_iter().flatten().collect::<Vec<_>>();
+            let ret = ret.unwrap_or(builder::any_type());
+            let kinds = extract_generics(&args, &ret);
+            let types = args.iter().map(ArgumentType::get_type).collect::<Vec<_>>();
+            Ok((s, (Type::Function(kinds, types, Box::new(ret), start.into()),
+                    Parameters::new(&args, &defaults))))
+        },
+        Err(r) => Err(r)
+    }
+}
+
+fn signature_type(s: Span) -> IResult<Span, (Type, Parameters)> {
+    alt((
+        named_parameters_type,
+        ltype.map(|typ| (typ, Parameters::default()))
+    )).parse(s)
+}
+
 fn signature_variable(s: Span) -> IResult<Span, Vec<Lang>> {
     let res = (tag("@"),
                 alt((variable_exp, custom_operators)),
                 terminated(tag(":"), multispace0),
-                ltype, 
+                signature_type, 
                 terminated(tag(";"), multispace0)).parse(s);
     match res {
-        Ok((s, (at, (name, h), _col, typ, _))) 
+        Ok((s, (at, (name, h), _col, (typ, params), _))) 
             => {
                 let var2 = Var::from_name(&name).set_help_data(h).set_type(typ.clone(), &Context::default());
-                Ok((s, vec![Lang::Signature(var2, typ, at.into())]))
+                Ok((s, vec![Lang::Signature(var2, typ, params, at.into())]))
             },
         Err(r) => Err(r)
     }
@@ -501,7 +669,7 @@ fn signature_opaque(s: Span) -> IResult<Span, Vec<Lang>> {
                     .set_opacity(true);
                 let t_alias = Type::Alias(name, params, path, true, h);
                 //let t_alias = Type::RClass([name].iter().cloned().collect::<HashSet<_>>(), h);
-                Ok((s, vec![Lang::Signature(var2, t_alias, at.into())]))
+                Ok((s, vec![Lang::Signature(var2, t_alias, Parameters::default(), at.into())]))
             },
         Ok((_s, (_, _, _))) => todo!(),
         Err(r) => Err(r)
@@ -536,7 +704,7 @@ fn for_loop(s: Span) -> IResult<Span, Vec<Lang>> {
 // main
 fn base_parse(s: Span) -> IResult<Span, Vec<Lang>> {
     let res = (opt(multispace0),
-        many0(alt((for_loop, signature, library, tests, import_type, import_var, mod_imp, comment, type_exp, mut_exp, opaque_exp, let_exp, module, assign, let_mut_exp, bangs_exp, simple_exp))),
+        many0(alt((for_loop, signature, library, tests, use_exp, mod_imp, comment, type_exp, mut_exp, opaque_exp, impl_exp, let_exp, module, assign, let_mut_exp, bangs_exp, simple_exp))),
         opt(alt((return_exp, parse_elements)))).parse(s);
     match res {
         Ok((s, (_, v, Some(exp)))) => {
@@ -697,6 +865,18 @@ mod tesus {
         assert_eq!(res, vec![]);
     }
 
+    #[test]
+    fn test_impl_block() {
+        let res = impl_exp("impl Addable for int { let add <- fn(a: int, b: int): int { a }; };".into()).unwrap().1;
+        assert!(matches!(&res[0], Lang::Impl(_, _, methods, _) if methods.len() == 1));
+    }
+
+    #[test]
+    fn test_let_mut() {
+        let res = base_mut_exp("let mut a <- 5;".into()).unwrap().1;
+        assert!(matches!(res, Lang::Let(var, _, _, _) if var.is_mutable() && !var.get_opacity()));
+    }
+
     #[test]
     fn test_base_parse3() {
         let res = base_mut_exp("mut a <- 5;".into()).unwrap().1;
@@ -863,6 +1043,24 @@ mod tesus {
         assert_eq!(res.0, vec![]);
     }
 
+    #[test]
+    fn test_use_selective() {
+        let res = use_exp("pub use geo::shapes::{area, Point};".into()).unwrap().1;
+        match &res[..] {
+            [Lang::Use(module, Some(names), true, _)] => {
+                assert_eq!(module, "geo::shapes");
+                assert_eq!(names, &vec!["area".to_string(), "Point".to_string()]);
+            },
+            _ => panic!("{:?}", res)
+        }
+    }
+
+    #[test]
+    fn test_use_glob() {
+        let res = use_exp("use shapes::*;".into()).unwrap().1;
+        assert_eq!(res, vec![Lang::Use("shapes".to_string(), None, false, HelpData::default())]);
+    }
+
     #[test]
     fn test_signature0() {
         let res = signature("@data: int;".into()).unwrap().1;
@@ -19,7 +19,7 @@ impl Path {
     }
 
     pub fn add_path(self, p: Path) -> Path {
-        Path(self.0 + "::" + &p.0)
+        if self.is_empty() { p } else { self + p }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -0,0 +1,90 @@
+use serde::Serialize;
+use crate::Type;
+use crate::var::Var;
+use crate::Context;
+use crate::help_data::HelpData;
+use crate::type_comparison::reduce_type;
+use crate::TypeError;
+use crate::help_message::ErrorMsg;
+
+// let {x, y} <- point;  let (a, {b, c}) <- pair;
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Pattern {
+    Var(Var),
+    Tuple(Vec<Pattern>, HelpData),
+    Record(Vec<(String, Pattern)>, HelpData), // field name, sub pattern
+}
+
+impl Pattern {
+    pub fn get_help_data(&self) -> HelpData {
+        match self {
+            Pattern::Var(var) => var.get_help_data(),
+            Pattern::Tuple(_, h) => h.clone(),
+            Pattern::Record(_, h) => h.clone(),
+        }
+    }
+
+    pub fn get_vars(&self) -> Vec<Var> {
+        match self {
+            Pattern::Var(var) => vec![var.clone()],
+            Pattern::Tuple(pats, _) => pats.iter().flat_map(Pattern::get_vars).collect(),
+            Pattern::Record(fields, _) => fields.iter().flat_map(|(_, pat)| pat.get_vars()).collect(),
+        }
+    }
+
+    /// Type of each bound variable, taken from the matching component of typ
+    pub fn bindings(&self, typ: &Type, context: &Context) -> Vec<(Var, Type)> {
+        match (self, reduce_type(context, typ)) {
+            (Pattern::Var(var), _) => vec![(var.clone(), typ.clone())],
+            (Pattern::Tuple(pats, h), Type::Tuple(types, _)) => {
+                if pats.len() != types.len() {
+                    panic!("{}", TypeError::WrongArity(h.clone(), typ.clone(), pats.len()).display())
+                }
+                pats.iter().zip(types.iter())
+                    .flat_map(|(pat, t)| pat.bindings(t, context))
+                    .collect()
+            },
+            (Pattern::Record(fields, _), Type::Record(args, _)) => {
+                fields.iter().flat_map(|(name, pat)| {
+                    let field_type = args.iter()
+                        .find(|arg_typ| &arg_typ.get_argument_str() == name)
+                        .map(|arg_typ| arg_typ.get_type())
+                        .unwrap_or_else(|| panic!("{}",
+                            TypeError::FieldNotFound((name.clone(), pat.get_help_data()), typ.clone()).display()));
+                    pat.bindings(&field_type, context)
+                }).collect()
+            },
+            (pat, _) => panic!("{}", TypeError::WrongPattern(pat.get_help_data(), typ.clone()).display())
+        }
+    }
+
+    /// R accessor of each bound variable starting from the value named root
+    pub fn extractions(&self, root: &str) -> Vec<(Var, String)> {
+        match self {
+            Pattern::Var(var) => vec![(var.clone(), root.to_string())],
+            Pattern::Tuple(pats, _) => pats.iter().enumerate()
+                .flat_map(|(i, pat)| pat.extractions(&format!("{}[[{}]]", root, i+1)))
+                .collect(),
+            Pattern::Record(fields, _) => fields.iter()
+                .flat_map(|(name, pat)| pat.extractions(&format!("{}[['{}']]", root, name)))
+                .collect(),
+        }
+    }
+
+    pub fn set_permission(self, perm: bool) -> Pattern {
+        match self {
+            Pattern::Var(var) => Pattern::Var(var.set_permission(perm)),
+            Pattern::Tuple(pats, h)
+                => Pattern::Tuple(pats.into_iter().map(|p| p.set_permission(perm)).collect(), h),
+            Pattern::Record(fields, h)
+                => Pattern::Record(fields.into_iter().map(|(n, p)| (n, p.set_permission(perm))).collect(), h),
+        }
+    }
+
+    pub fn temporary_name(&self) -> String {
+        let names = self.get_vars().iter()
+            .map(Var::get_name)
+            .collect::<Vec<_>>();
+        format!(".{}", names.join("_"))
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Type;
+use crate::argument_type::ArgumentType;
+use crate::help_data::HelpData;
+
+// A row variable *$L: *T (or *{$L: T}) stands for the fields of a record
+// that the other fields of the pattern don't name: {*$L: *T, id: int}
+// matches any record with an id, $L and T get the labels and types of the
+// other fields as tuples, and a substitution puts them back as fields.
+// The same goes for the tuples: {*T} matches any tuple and T gets its types.
+
+fn is_row(arg_typ: &ArgumentType) -> bool {
+    matches!(arg_typ.get_argument(), Type::Multi(_, _))
+}
+
+pub fn has_row(fields: &[ArgumentType]) -> bool {
+    fields.iter().any(is_row)
+}
+
+// the received fields for the fixed fields of the pattern, and the rest
+type Split = (Vec<(ArgumentType, ArgumentType)>, Vec<ArgumentType>);
+
+fn split_row(fields: &[ArgumentType], pattern: &[ArgumentType]) -> Option<Split> {
+    let fixed = pattern.iter()
+        .filter(|arg_typ| !is_row(arg_typ))
+        .map(|sup| fields.iter()
+             .find(|sub| sub.get_argument_str() == sup.get_argument_str())
+             .map(|sub| (sub.clone(), sup.clone())))
+        .collect::<Option<Vec<_>>>()?;
+    let rest = fields.iter()
+        .filter(|sub| !fixed.iter().any(|(arg_typ, _)| arg_typ.get_argument_str() == sub.get_argument_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    // a lone row variable doesn't match the empty record
+    (!(fixed.is_empty() && rest.is_empty())).then_some((fixed, rest))
+}
+
+pub fn row_subtype(fields: &[ArgumentType], pattern: &[ArgumentType], is_subtype: impl Fn(&Type, &Type) -> bool) -> bool {
+    split_row(fields, pattern)
+        .map(|(fixed, _)| fixed.iter().all(|(sub, sup)| is_subtype(&sub.get_type(), &sup.get_type())))
+        .unwrap_or(false)
+}
+
+/// Inside the body of its function a row variable is rigid: it stands for
+/// fields the body doesn't know, so only a value keeping the row of a
+/// parameter can be returned for it
+pub fn keeps_rows(received: &Type, expected: &Type) -> bool {
+    match (received, expected) {
+        (Type::Record(fields1, _), Type::Record(fields2, _))
+            => fields2.iter().filter(|arg_typ| is_row(arg_typ)).all(|row| fields1.contains(row)),
+        (Type::Tuple(types1, _), Type::Tuple(types2, _))
+            => types2.iter().filter(|typ| matches!(typ, Type::Multi(_, _))).all(|row| types1.contains(row)),
+        _ => true
+    }
+}
+
+/// Pairs (received, pattern) to unify: the types of the fixed fields, then
+/// the labels and the types of the rest for the row variable
+pub fn row_bindings(fields: &[ArgumentType], pattern: &[ArgumentType]) -> Option<Vec<(Type, Type)>> {
+    let (fixed, rest) = split_row(fields, pattern)?;
+    let h = HelpData::default();
+    let (labels, types): (Vec<_>, Vec<_>) = rest.iter()
+        .map(|arg_typ| (arg_typ.get_argument(), arg_typ.get_type()))
+        .unzip();
+    let row = pattern.iter()
+        .filter(|arg_typ| is_row(arg_typ))
+        .flat_map(|arg_typ| match (arg_typ.get_argument(), arg_typ.get_type()) {
+            (Type::Multi(label, _), Type::Multi(typ, _))
+                => vec![(Type::Tuple(labels.clone(), h.clone()), *label),
+                        (Type::Tuple(types.clone(), h.clone()), *typ)],
+            (Type::Multi(label, _), _) => vec![(Type::Tuple(labels.clone(), h.clone()), *label)],
+            _ => vec![]
+        });
+    Some(fixed.iter()
+        .map(|(sub, sup)| (sub.get_type(), sup.get_type()))
+        .chain(row)
+        .collect())
+}
+
+/// A row variable whose labels and types are known becomes their fields
+pub fn expand_row(label: &Type, typ: &Type) -> Option<Vec<ArgumentType>> {
+    match (label, typ) {
+        (Type::Tuple(labels, _), Type::Tuple(types, _)) if labels.len() == types.len()
+            => Some(labels.iter().zip(types.iter())
+                    .map(|(label, typ)| ArgumentType(label.clone(), typ.clone(), false))
+                    .collect()),
+        _ => None
+    }
+}
+
+/// The generic of {*T}, the pattern of any tuple
+pub fn tuple_row(types: &[Type]) -> Option<Type> {
+    match types {
+        [Type::Multi(typ, _)] => Some((**typ).clone()),
+        _ => None
+    }
+}
+
+// {1, *T} becomes {1, int, char} once T is {int, char}
+pub fn expand_tuple(types: &[Type]) -> Vec<Type> {
+    types.iter()
+        .flat_map(|typ| match typ {
+            Type::Multi(inner, _) => match &**inner {
+                Type::Tuple(types, _) => types.clone(),
+                _ => vec![typ.clone()]
+            },
+            _ => vec![typ.clone()]
+        }).collect()
+}
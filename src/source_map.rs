@@ -0,0 +1,130 @@
+use serde::{Serialize, Deserialize};
+use crate::help_data::HelpData;
+use std::path::PathBuf;
+use std::fs;
+
+// the R lines r_start..=r_end were generated from ty_file at ty_line:ty_column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mapping {
+    pub r_start: usize,
+    pub r_end: usize,
+    pub ty_file: String,
+    pub ty_line: usize,
+    pub ty_column: usize,
+}
+
+// written next to each generated file as <name>.R.map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SourceMap {
+    pub r_file: String,
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// chunks: the R code of each statement, joined by new lines from first_line on
+    pub fn new(r_file: &str, first_line: usize, chunks: &[(String, HelpData)]) -> SourceMap {
+        let mut line = first_line;
+        let mappings = chunks.iter().filter_map(|(code, h)| {
+            let start = line;
+            line += code.split('\n').count();
+            h.get_position().map(|(ty_line, ty_column)| Mapping {
+                r_start: start,
+                r_end: line - 1,
+                ty_file: h.get_file_name(),
+                ty_line,
+                ty_column,
+            })
+        }).collect();
+        SourceMap { r_file: r_file.to_string(), mappings }
+    }
+
+    pub fn find(&self, r_line: usize) -> Option<&Mapping> {
+        self.mappings.iter()
+            .find(|m| m.r_start <= r_line && r_line <= m.r_end)
+    }
+
+    pub fn write(&self, path: &PathBuf) {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, json).unwrap_or_else(|_| panic!("Can't write the source map {}", path.display()));
+    }
+
+    pub fn read(path: &PathBuf) -> Option<SourceMap> {
+        fs::read_to_string(path).ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    /// Replace the references to the R file ("main.R#12", "main.R:12:3")
+    /// by the location of the TypR code that produced them
+    pub fn rewrite(&self, text: &str) -> String {
+        let mut res = String::new();
+        let mut rest = text;
+        while let Some(pos) = rest.find(&self.r_file) {
+            let after = &rest[pos + self.r_file.len()..];
+            let line = reference_digits(after, &['#', ':']);
+            match line.and_then(|l| l.parse().ok()).and_then(|l| self.find(l)) {
+                Some(m) => {
+                    let after_line = &after[1 + line.unwrap().len()..];
+                    let column = reference_digits(after_line, &[':']);
+                    res.push_str(&rest[..pos]);
+                    res.push_str(&format!("{}:{}:{}", m.ty_file, m.ty_line, m.ty_column));
+                    rest = &after_line[column.map(|c| c.len() + 1).unwrap_or(0)..];
+                },
+                None => {
+                    res.push_str(&rest[..pos + self.r_file.len()]);
+                    rest = after;
+                }
+            }
+        }
+        res + rest
+    }
+}
+
+// "#12 ..." -> Some("12")
+fn reference_digits<'a>(s: &'a str, separators: &[char]) -> Option<&'a str> {
+    let digits = s.strip_prefix(separators)?;
+    let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+    (end > 0).then(|| &digits[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_map() -> SourceMap {
+        SourceMap {
+            r_file: "main.R".to_string(),
+            mappings: vec![
+                Mapping { r_start: 5, r_end: 7, ty_file: "main.ty".to_string(), ty_line: 2, ty_column: 1 },
+                Mapping { r_start: 8, r_end: 8, ty_file: "main.ty".to_string(), ty_line: 4, ty_column: 3 },
+            ]
+        }
+    }
+
+    #[test]
+    fn test_rewrite_error_location() {
+        let res = source_map().rewrite("Error in f(x) (from main.R#6) : wrong");
+        assert_eq!(res, "Error in f(x) (from main.ty:2:1) : wrong");
+    }
+
+    #[test]
+    fn test_rewrite_parse_error_and_unknown_line() {
+        let res = source_map().rewrite("main.R:8:12: unexpected ')'\nat main.R#40");
+        assert_eq!(res, "main.ty:4:3: unexpected ')'\nat main.R#40");
+    }
+
+    #[test]
+    fn test_lines_of_chunks() {
+        let file = std::env::temp_dir().join("typr_source_map.ty");
+        let code = "let a <- 1;\n  let b <- 2;\n";
+        fs::write(&file, code).unwrap();
+        let adt = crate::parse(nom_locate::LocatedSpan::new_extra(code, file.display().to_string())).unwrap().1;
+        let chunks = vec![
+            ("a <- 1L".to_string(), adt.0[0].get_help_data()),
+            ("b <- (function() {\n 2L \n})()".to_string(), adt.0[1].get_help_data()),
+        ];
+        let map = SourceMap::new("main.R", 3, &chunks);
+        assert_eq!(map.find(3).map(|m| (m.ty_line, m.ty_column)), Some((1, 1)));
+        assert_eq!(map.find(6).map(|m| (m.ty_line, m.ty_column)), Some((2, 3)));
+        assert_eq!(map.find(7), None);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::Type;
+use crate::Lang;
+use crate::Context;
+use crate::TypeError;
+use crate::builder;
+use crate::help_message::ErrorMsg;
+use crate::help_data::HelpData;
+use crate::argument_type::ArgumentType;
+use crate::tint::Tint;
+use crate::var::Var;
+use crate::parameters::named_argument;
+use crate::type_checker::typing;
+
+// dplyr verbs: select(df, id, score), mutate(df, total = id + score),
+// filter(df, score > 2.0), arrange(df, id), summarise(df, best = max(score)).
+// The bare column names are labels of the schema, so a typo is an error
+// here instead of an "object not found" when R runs.
+// A verb takes any number of columns, which a header signature can't say,
+// and filter keeps the meaning of std.R for a vector: the verbs are only
+// checked here, when their data is a record or a data frame.
+
+// the rows (None for a record) and the fields of the data
+fn columns(typ: &Type) -> Option<(Option<Type>, Vec<ArgumentType>)> {
+    match typ {
+        Type::Record(fields, _) => Some((None, fields.clone())),
+        Type::DataFrame(rows, schema, _) => match &**schema {
+            Type::Record(fields, _) => Some((Some((**rows).clone()), fields.clone())),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn rebuild(data: &Type, rows: Option<Type>, fields: Vec<ArgumentType>) -> Type {
+    let h = data.get_help_data();
+    match rows {
+        Some(rows) => Type::DataFrame(Box::new(rows), Box::new(Type::Record(fields, h.clone())), h),
+        None => Type::Record(fields, h)
+    }
+}
+
+// select(df, id, score): each column must be a label of the schema
+fn column_names(verb: &str, data: &Type, fields: &[ArgumentType], values: &[Lang]) -> Vec<ArgumentType> {
+    values.iter()
+        .map(|value| match value {
+            Lang::Variable(name, _, _, _, _, h) | Lang::Char(name, h) => fields.iter()
+                .find(|arg_typ| arg_typ.get_argument_str() == *name)
+                .cloned()
+                .unwrap_or_else(|| panic!("{}", TypeError::FieldNotFound((name.clone(), h.clone()), data.clone()).display())),
+            _ => panic!("{}", TypeError::ColumnExpected(verb.to_string(), value.get_help_data()).display())
+        }).collect()
+}
+
+// the columns are variables inside the expressions of a verb: one value
+// for each row (mutate, filter) or the whole column (summarise)
+fn column_context(context: &Context, rows: &Option<Type>, fields: &[ArgumentType], whole_column: bool) -> Context {
+    fields.iter()
+        .fold(context.clone(), |ctx, arg_typ| {
+            let typ = match rows {
+                Some(rows) if whole_column
+                    => Type::Array(Box::new(rows.clone()), Box::new(arg_typ.get_type()), arg_typ.get_type().get_help_data()),
+                _ => arg_typ.get_type()
+            };
+            ctx.push_var_type(Var::from_name(&arg_typ.get_argument_str()), typ, context)
+        })
+}
+
+// mutate(df, total = id + score): the new columns replace or extend the schema
+fn new_columns(verb: &str, context: &Context, fields: Vec<ArgumentType>, values: &[Lang]) -> Vec<ArgumentType> {
+    values.iter()
+        .fold(fields, |fields, value| {
+            let (name, expr) = named_argument(value)
+                .unwrap_or_else(|| panic!("{}", TypeError::ColumnExpected(verb.to_string(), value.get_help_data()).display()));
+            let column = ArgumentType::new(&name, &typing(context, &expr).0);
+            if fields.iter().any(|arg_typ| arg_typ.get_argument_str() == name) {
+                fields.into_iter()
+                    .map(|arg_typ| if arg_typ.get_argument_str() == name { column.clone() } else { arg_typ })
+                    .collect()
+            } else {
+                fields.into_iter().chain([column]).collect()
+            }
+        })
+}
+
+fn verb_type(context: &Context, verb: &str, data: &Type, values: &[Lang], h: &HelpData) -> Type {
+    let (rows, fields) = columns(data).unwrap();
+    match verb {
+        "select" => rebuild(data, rows, column_names(verb, data, &fields, values)),
+        "arrange" => {
+            column_names(verb, data, &fields, values);
+            data.clone()
+        },
+        "filter" => {
+            let row_context = column_context(context, &rows, &fields, false);
+            let not_boolean = values.iter()
+                .map(|value| (value, typing(&row_context, value).0))
+                .find(|(_, typ)| !typ.reduce(context).is_boolean());
+            if let Some((value, typ)) = not_boolean {
+                panic!("{}", TypeError::Param(builder::boolean_type().set_help_data(value.get_help_data()), typ).display())
+            }
+            rebuild(data, rows.map(|_| Type::Integer(Tint::Unknown, h.clone())), fields)
+        },
+        "mutate" => {
+            let row_context = column_context(context, &rows, &fields, false);
+            rebuild(data, rows, new_columns(verb, &row_context, fields, values))
+        },
+        _ => {
+            let column_context = column_context(context, &rows, &fields, true);
+            let summaries = new_columns(verb, &column_context, vec![], values);
+            rebuild(data, rows.map(|_| Type::Integer(Tint::Val(1), h.clone())), summaries)
+        }
+    }
+}
+
+// a declared function taking the data as its first argument shadows the verb,
+// as the one of the user or the filter of std.ty for a vector
+fn is_shadowed(context: &Context, fn_name: &str, data: &Type) -> bool {
+    context.get_signatures(&Var::from(fn_name)).iter()
+        .filter_map(|signature| signature.to_function_type()?.get_first_param())
+        .any(|param| data.is_subtype(&param, context))
+}
+
+/// Type of the dplyr verbs applied to a record or a data frame, None for the other calls
+pub fn tidy_call(context: &Context, fn_name: &str, values: &[Lang], h: &HelpData) -> Option<Type> {
+    match (fn_name, values) {
+        ("select" | "mutate" | "filter" | "arrange" | "summarise", [data, rest @ ..]) => {
+            let data = typing(context, data).0.reduce(context);
+            match &data {
+                _ if is_shadowed(context, fn_name, &data) => None,
+                // the columns of a data frame read from a file are unknown
+                Type::DataFrame(_, schema, _) if schema.is_any() => Some(data.clone()),
+                typ if columns(typ).is_some() => Some(verb_type(context, fn_name, &data, rest, h)),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
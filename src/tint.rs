@@ -34,8 +34,8 @@ impl Add for Tint {
     fn add(self, other: Self) -> Self {
         match (self, other) {
             (Tint::Val(i1), Tint::Val(i2)) => Tint::Val(i1+i2),
-            (Tint::Unknown, t) => panic!("Can't add opaque int type with {:?}", t),
-            (t, Tint::Unknown) => panic!("Can't add {:?} with opaque int type", t)
+            // an unknown length stays unknown
+            _ => Tint::Unknown
         }
     }
 }
@@ -45,8 +45,8 @@ impl Sub for Tint {
     fn sub(self, other: Self) -> Self {
         match (self, other) {
             (Tint::Val(i1), Tint::Val(i2)) => Tint::Val(i1-i2),
-            (Tint::Unknown, t) => panic!("Can't substract opaque int type with {:?}", t),
-            (t, Tint::Unknown) => panic!("Can't substract {:?} with opaque int type", t)
+            // an unknown length stays unknown
+            _ => Tint::Unknown
         }
     }
 }
@@ -56,8 +56,8 @@ impl Mul for Tint {
     fn mul(self, other: Self) -> Self {
         match (self, other) {
             (Tint::Val(i1), Tint::Val(i2)) => Tint::Val(i1*i2),
-            (Tint::Unknown, t) => panic!("Can't substract opaque int type with {:?}", t),
-            (t, Tint::Unknown) => panic!("Can't substract {:?} with opaque int type", t)
+            // an unknown length stays unknown
+            _ => Tint::Unknown
         }
     }
 }
@@ -67,8 +67,8 @@ impl Div for Tint {
     fn div(self, other: Self) -> Self {
         match (self, other) {
             (Tint::Val(i1), Tint::Val(i2)) => Tint::Val(i1/i2),
-            (Tint::Unknown, t) => panic!("Can't substract opaque int type with {:?}", t),
-            (t, Tint::Unknown) => panic!("Can't substract {:?} with opaque int type", t)
+            // an unknown length stays unknown
+            _ => Tint::Unknown
         }
     }
 }
@@ -118,4 +118,10 @@ mod tests {
     fn test_tint_val(){
         assert_eq!(Tint::Val(2), Tint::Val(3));
     }
+
+    #[test]
+    fn test_tint_unknown_arithmetic(){
+        assert_eq!(Tint::Val(2) + Tint::Unknown, Tint::Unknown);
+        assert_eq!(Tint::Unknown * Tint::Val(3), Tint::Unknown);
+    }
 }
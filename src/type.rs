@@ -23,9 +23,16 @@ use crate::type_comparison::reduce_type;
 use std::hash::Hash;
 use std::hash::Hasher;
 use crate::builder;
+use crate::index_solver::simplify;
+use crate::index_solver::is_index_expression;
+use crate::condition::satisfies;
+use crate::row::has_row;
+use crate::row::row_subtype;
+use crate::row::tuple_row;
 use crate::type_category::TypeCategory;
 use crate::type_checker::match_types;
 use crate::type_comparison::is_matching;
+use crate::type_comparison;
 use crate::TypeError;
 use crate::help_message::ErrorMsg;
 use crate::Var;
@@ -70,8 +77,14 @@ pub enum Type {
     If(Box<Type>, Vec<Type>, HelpData),
     Condition(Box<Type>, Box<Type>, Box<Type>, HelpData),
     In(HelpData),
+    Comparison(String, HelpData), // <, <=, >, >=, == or != in a condition
     RFunction(HelpData),
     RClass(HashSet<String>, HelpData),
+    Formula(Vec<String>, HelpData), // variables used by the formula
+    Guard(String, Box<Type>, HelpData), // bool telling that the parameter is of the type
+    Nullable(Box<Type>, HelpData), // T? can be NA
+    DataFrame(Box<Type>, Box<Type>, HelpData), // number of rows, record of the columns
+    Factor(Vec<String>, HelpData), // levels in their order
     Empty(HelpData),
     Any(HelpData)
 }
@@ -86,7 +99,7 @@ impl Type {
         (!other.is_empty())
             .then_some(is_matching(context, self, other)
                             .then_some(other.clone())
-                            .expect(&TypeError::Let(other.clone(), self.clone()).display()))
+                            .unwrap_or_else(|| panic!("{}", TypeError::Let(other.clone(), self.clone()).display())))
             .unwrap_or(self.clone())
     }
 
@@ -108,6 +121,24 @@ impl Type {
         .collect()
     }
 
+    /// A reduced type that still names an alias: a recursive type folded in
+    /// its expansion or an opaque type, which are both compared by name
+    pub fn keeps_alias(&self) -> bool {
+        match self {
+            Type::Alias(_, _, _, _, _) => true,
+            Type::Record(args, _) | Type::Interface(args, _)
+                => args.iter().any(|arg_typ| arg_typ.get_type().keeps_alias()),
+            Type::StrictUnion(tags, _) => tags.iter().any(|tag| tag.to_type().keeps_alias()),
+            Type::Union(types, _) => types.iter().any(Type::keeps_alias),
+            Type::Tuple(types, _) | Type::Params(types, _) => types.iter().any(Type::keeps_alias),
+            Type::Function(_, args, ret, _)
+                => args.iter().any(Type::keeps_alias) || ret.keeps_alias(),
+            Type::Tag(_, typ, _) | Type::Nullable(typ, _) | Type::Array(_, typ, _)
+                => typ.keeps_alias(),
+            _ => false
+        }
+    }
+
     pub fn get_label(&self) -> String {
         match self {
             Type::Char(l, _) => l.to_string(),
@@ -118,13 +149,13 @@ impl Type {
 
     fn add_kinds_in_functions_if_not(&self) -> Type {
         match self {
-            Type::Function(_kinds, args, ret, h) => {
+            Type::Function(kinds, args, ret, h) => {
                 let new_kinds = args.iter()
                     .chain([(**ret).clone()].iter())
                     .flat_map(|typ| typ.extract_generics())
                     .collect::<HashSet<_>>()
                     .iter()
-                    .map(|typ| ArgumentKind::from((typ.clone(), typ.get_kind())))
+                    .map(|typ| ArgumentKind::from((typ.clone(), typ.get_kind())).with_bound_from(kinds))
                     .collect::<Vec<_>>();
                 Type::Function(new_kinds, args.clone(), ret.clone(), h.clone())
             },
@@ -244,6 +275,7 @@ impl Type {
         match self {
             Type::Generic(_, _) | Type::IndexGen(_, _) | Type::LabelGen(_, _)
                 => vec![self.clone()],
+            Type::Multi(typ, _) => typ.extract_generics(),
             Type::Function(kinds, args, ret_typ, _) => {
                 kinds.iter()
                     .map(|ak| ak.get_argument())
@@ -294,14 +326,14 @@ impl Type {
                     Box::new(ind.index_calculation()), 
                     Box::new(typ.index_calculation()),
                     h.clone()),
-                    Type::Function(_kinds, args, ret_typ, h) => {
+                    Type::Function(kinds, args, ret_typ, h) => {
                         let new_args = args.iter()
                             .map(|typ| typ.index_calculation())
                             .collect::<Vec<_>>();
                         let new_kinds = args.iter()
                             .flat_map(|typ| typ.extract_generics())
                             .collect::<HashSet<_>>().iter()
-                            .map(|typ| ArgumentKind::from((typ.clone(), typ.get_kind())))
+                            .map(|typ| ArgumentKind::from((typ.clone(), typ.get_kind())).with_bound_from(kinds))
                             .collect::<Vec<_>>();
                         Type::Function(
                             new_kinds,
@@ -312,6 +344,12 @@ impl Type {
         }
     }
 
+    // an index expression with generics (#N+1) stays symbolic
+    fn is_index(&self) -> bool {
+        matches!(self, Type::Integer(_, _) | Type::IndexGen(_, _)
+                 | Type::Add(_, _, _) | Type::Minus(_, _, _) | Type::Mul(_, _, _) | Type::Div(_, _, _))
+    }
+
     fn sum_index(&self, i: &Type) -> Type {
         match (self, i) {
             (Type::Integer(a, h), Type::Integer(b, _)) => Type::Integer(*a+*b, h.clone()),
@@ -321,6 +359,8 @@ impl Type {
                     .chain(b.iter())
                     .cloned()
                     .collect::<Vec<_>>(), h.clone()),
+            (a, b) if a.is_index() && b.is_index()
+                => simplify(&Type::Add(Box::new(a.clone()), Box::new(b.clone()), a.get_help_data())),
             _ => panic!("Type {} and {} can't be added", self, i)
         }
     }
@@ -328,6 +368,8 @@ impl Type {
     fn minus_index(&self, i: &Type) -> Type {
         match (self, i) {
             (Type::Integer(a, h), Type::Integer(b, _)) => Type::Integer(*a-*b, h.clone()),
+            (a, b) if a.is_index() && b.is_index()
+                => simplify(&Type::Minus(Box::new(a.clone()), Box::new(b.clone()), a.get_help_data())),
             _ => panic!("Type {} and {} can't be added", self, i)
         }
     }
@@ -335,6 +377,8 @@ impl Type {
     fn mul_index(&self, i: &Type) -> Type {
         match (self, i) {
             (Type::Integer(a, h), Type::Integer(b, _)) => Type::Integer(*a*(*b), h.clone()),
+            (a, b) if a.is_index() && b.is_index()
+                => simplify(&Type::Mul(Box::new(a.clone()), Box::new(b.clone()), a.get_help_data())),
             _ => panic!("Type {} and {} can't be added", self, i)
         }
     }
@@ -342,6 +386,8 @@ impl Type {
     fn div_index(&self, i: &Type) -> Type {
         match (self, i) {
             (Type::Integer(a, h), Type::Integer(b, _)) => Type::Integer(*a/(*b), h.clone()),
+            (a, b) if a.is_index() && b.is_index()
+                => simplify(&Type::Div(Box::new(a.clone()), Box::new(b.clone()), a.get_help_data())),
             _ => panic!("Type {} and {} can't be added", self, i)
         }
     }
@@ -445,8 +491,16 @@ impl Type {
     }
 
     pub fn is_subtype(&self, other: &Type, context: &Context) -> bool {
+        if let (Type::Alias(name1, params1, path1, _, _), Type::Alias(name2, params2, path2, _, _)) = (self, other) {
+            // a recursive type compares by its parameters instead of unfolding forever
+            if name1 == name2 && path1 == path2 {
+                return params1.len() == params2.len() &&
+                    params1.iter().zip(params2.iter()).all(|(t1, t2)| t1.is_subtype(t2, context))
+            }
+        }
         match (self.reduce(context), other.reduce(context)) {
             (Type::Empty(_), _) => true,
+            (typ1, _) if !satisfies(context, &typ1, other) => false,
             (typ1, typ2) if typ1 == typ2 => true,
             // Array subtyping
             (_, Type::Any(_)) => true,
@@ -459,18 +513,21 @@ impl Type {
                     .all(|(typ1, typ2)| typ1.is_subtype(typ2, context))
             }
             // Interface subtyping
-            (_type1, Type::Interface(_args, _)) => {
-                todo!();
+            (type1, typ2 @ Type::Interface(_, _)) => {
+                type_comparison::is_subtype(context, &type1, &typ2)
             }
 
             // Record subtyping
+            (Type::Tuple(_, _), Type::Tuple(types, _)) if tuple_row(&types).is_some() => true,
+            (Type::Record(r1, _), Type::Record(r2, _)) if has_row(&r2)
+                => row_subtype(&r1, &r2, |typ1, typ2| typ1.is_subtype(typ2, context)),
             (Type::Record(r1, _), Type::Record(r2, _)) => {
                 if has_generic_label(&r2) && (r1.len() == r2.len()) {
                     all_subtype2(&r1, &r2)
                 } else if let Some(_arg_typ) = other.get_type_pattern() {
                     true
                 } else {
-                    contains_all2(&r1, &r2)
+                    contains_all2(context, &r1, &r2)
                 }
             },
 
@@ -491,6 +548,8 @@ impl Type {
             (Type::Integer(_, _), Type::IndexGen(_, _)) => true,
             (Type::Char(_, _), Type::LabelGen(_, _)) => true,
             (Type::IndexGen(_, _), Type::IndexGen(_, _)) => true,
+            (Type::Integer(_, _) | Type::IndexGen(_, _), expr @ (Type::Add(_, _, _) | Type::Minus(_, _, _) | Type::Mul(_, _, _) | Type::Div(_, _, _)))
+                => is_index_expression(&expr),
 
             // Params subtyping
             (Type::Params(p1, _), Type::Params(p2, _)) => {
@@ -499,8 +558,23 @@ impl Type {
             }
 
             (Type::RClass(set1, _), Type::RClass(set2, _)) => set1.is_subset(&set2),
-            (Type::Union(s1, _), Type::Union(s2, _)) => s1.is_subset(&s2),
-            (typ, Type::Union(s2, _)) => s2.contains(&typ),
+            // the type Formula is any formula, else its variables must be allowed
+            (Type::Formula(vars1, _), Type::Formula(vars2, _))
+                => vars2.is_empty() || vars1.iter().all(|var| vars2.contains(var)),
+            (Type::Guard(_, _, _), Type::Boolean(_)) => true,
+            (Type::Nullable(t1, _), Type::Nullable(t2, _)) => t1.is_subtype(&t2, context),
+            (t1, Type::Nullable(t2, _)) => t1.is_subtype(&t2, context),
+            // a data frame read from a file has columns of unknown type
+            (Type::DataFrame(_, schema, _), Type::DataFrame(_, _, _)) if schema.is_any() => true,
+            (Type::DataFrame(rows1, schema1, _), Type::DataFrame(rows2, schema2, _))
+                => rows1.is_subtype(&rows2, context) && schema1.is_subtype(&schema2, context),
+            (Type::Char(Tchar::Val(level), _), Type::Factor(levels, _)) => levels.contains(&level),
+            (Type::Factor(levels1, _), Type::Factor(levels2, _))
+                => levels1.iter().all(|level| levels2.contains(level)),
+            (Type::Union(s1, _), Type::Union(s2, _)) => {
+                s1.iter().all(|t1| s2.iter().any(|t2| t1.is_subtype(t2, context)))
+            },
+            (typ, Type::Union(s2, _)) => s2.iter().any(|t| typ.is_subtype(t, context)),
 
             (Type::Char(_, _), Type::Char(_, _)) => true,
             (Type::Integer(_, _), Type::Integer(_, _)) => true,
@@ -529,6 +603,11 @@ impl Type {
             Type::Any(_) => TypeCategory::Any,
             Type::Empty(_) => TypeCategory::Empty,
             Type::RClass(_, _) => TypeCategory::RClass,
+            Type::Formula(_, _) => TypeCategory::Formula,
+            Type::Guard(_, _, _) => TypeCategory::Boolean,
+            Type::Nullable(typ, _) => typ.to_category(),
+            Type::DataFrame(_, _, _) => TypeCategory::DataFrame,
+            Type::Factor(_, _) => TypeCategory::Factor,
             Type::RFunction(_) => TypeCategory::RFunction,
             Type::Alias(name, _, _, _, _) => TypeCategory::Opaque(name.clone()),
             Type::Union(_, _) => TypeCategory::Union,
@@ -599,11 +678,17 @@ impl Type {
             Type::If(_, _, h) => h.clone(),
             Type::Condition(_, _, _, h) => h.clone(),
             Type::In(h) => h.clone(),
+            Type::Comparison(_, h) => h.clone(),
             Type::RFunction(h) => h.clone(),
             Type::Empty(h) => h.clone(),
             Type::Any(h) => h.clone(),
             Type::RClass(_, h) => h.clone(),
             Type::Union(_, h) => h.clone(),
+            Type::Formula(_, h) => h.clone(),
+            Type::Guard(_, _, h) => h.clone(),
+            Type::Nullable(_, h) => h.clone(),
+            Type::DataFrame(_, _, h) => h.clone(),
+            Type::Factor(_, h) => h.clone(),
         }
     }
 
@@ -636,11 +721,17 @@ impl Type {
             Type::If(a1, a2, _) => Type::If(a1, a2, h2),
             Type::Condition(a1, a2, a3, _) => Type::Condition(a1, a2, a3, h2),
             Type::In(_) => Type::In(h2),
+            Type::Comparison(a, _) => Type::Comparison(a, h2),
             Type::RFunction(_) => Type::RFunction(h2),
             Type::Empty(_) => Type::Empty(h2),
             Type::Any(_) => Type::Any(h2),
             Type::RClass(v, _) => Type::RClass(v, h2),
-            Type::Union(v, _) => Type::Union(v, h2) 
+            Type::Union(v, _) => Type::Union(v, h2),
+            Type::Formula(v, _) => Type::Formula(v, h2),
+            Type::Guard(name, typ, _) => Type::Guard(name, typ, h2),
+            Type::Nullable(typ, _) => Type::Nullable(typ, h2),
+            Type::DataFrame(rows, schema, _) => Type::DataFrame(rows, schema, h2),
+            Type::Factor(levels, _) => Type::Factor(levels, h2)
         }
     }
 
@@ -705,7 +796,7 @@ impl From<Type> for HelpData {
            Type::Boolean(h) => h,
            Type::Array(_, _, h) => h,
            Type::Number(h) => h,
-           e => panic!("The type element {:?} is not yet implemented", e)
+           e => e.get_help_data()
        }.clone()
    } 
 }
@@ -750,6 +841,12 @@ impl PartialEq for Type {
             (Type::Condition(a1, b1, c1, _), Type::Condition(a2, b2, c2, _)) 
                 => a1 == a2 && b1 == b2 && c1 == c2,
             (Type::In(_), Type::In(_)) => true,
+            (Type::Comparison(e1, _), Type::Comparison(e2, _)) => e1 == e2,
+            (Type::Formula(vars1, _), Type::Formula(vars2, _)) => vars1 == vars2,
+            (Type::Guard(n1, t1, _), Type::Guard(n2, t2, _)) => n1 == n2 && t1 == t2,
+            (Type::Nullable(t1, _), Type::Nullable(t2, _)) => t1 == t2,
+            (Type::DataFrame(r1, s1, _), Type::DataFrame(r2, s2, _)) => r1 == r2 && s1 == s2,
+            (Type::Factor(l1, _), Type::Factor(l2, _)) => l1 == l2,
             (Type::Empty(_), Type::Empty(_)) => true,
             (Type::Any(_), Type::Any(_)) => true,
             (Type::RClass(el1, _), Type::RClass(el2, _)) 
@@ -835,6 +932,12 @@ impl Hash for Type {
             Type::RFunction(_) => 30.hash(state),
             Type::RClass(_, _) => 31.hash(state),
             Type::Union(_, _) => 33.hash(state),
+            Type::Formula(_, _) => 34.hash(state),
+            Type::Guard(name, _, _) => { 35.hash(state); name.hash(state) },
+            Type::Nullable(typ, _) => { 36.hash(state); typ.hash(state) },
+            Type::DataFrame(_, schema, _) => { 37.hash(state); schema.hash(state) },
+            Type::Factor(levels, _) => { 38.hash(state); levels.hash(state) },
+            Type::Comparison(ope, _) => { 39.hash(state); ope.hash(state) },
         }
     }
 }
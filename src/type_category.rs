@@ -18,11 +18,13 @@ pub enum TypeCategory {
     Char,
     Generic,
     DataFrame,
+    Factor,
     Alias,
     Any,
     Empty,
     RClass,
     RFunction,
+    Formula,
     Opaque(String),
     Template,
     Rest
@@ -50,6 +52,7 @@ impl fmt::Display for TypeCategory {
             TypeCategory::Integer => "integer",
             TypeCategory::Number => "numeric",
             TypeCategory::DataFrame => "data.frame",
+            TypeCategory::Factor => "factor",
             TypeCategory::Char => "character",
             TypeCategory::Generic => "Generic",
             TypeCategory::Alias => "Alias",
@@ -57,6 +60,7 @@ impl fmt::Display for TypeCategory {
             TypeCategory::Empty => "Empty",
             TypeCategory::RClass => "RClass",
             TypeCategory::RFunction => "RFunction",
+            TypeCategory::Formula => "formula",
             TypeCategory::Tuple => "Tuple",
             TypeCategory::Opaque(name) => &name.to_string(),
             TypeCategory::Template => "Template",
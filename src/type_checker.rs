@@ -7,6 +7,8 @@ use crate::Lang;
 use crate::var::Var;
 use crate::tag::Tag;
 use crate::index::Index;
+use crate::tint::Tint;
+use crate::tchar::Tchar;
 use crate::unification;
 use crate::type_comparison::reduce_type;
 use crate::argument_type::ArgumentType;
@@ -21,11 +23,37 @@ use crate::help_data::HelpData;
 use crate::builder;
 use crate::TypeError;
 use crate::help_message::ErrorMsg;
+use crate::help_message::TypeWarning;
 use std::error::Error;
 use crate::argument_value::ArgumentValue;
 use crate::typer::Typer;
 use crate::type_comparison::is_matching;
 use crate::function_type::FunctionType;
+use crate::inference::needs_inference;
+use crate::inference::infer_function;
+use crate::elements::extract_generics;
+use crate::argument_kind::ArgumentKind;
+use crate::kind::Kind;
+use crate::narrowing::narrow;
+use crate::nullable::propagates_na;
+use crate::nullable::is_nullable;
+use crate::nullable::nullable;
+use crate::nullable::non_null;
+use crate::data_frame::data_frame_call;
+use crate::data_frame::column_type;
+use crate::tidy::tidy_call;
+use crate::factor::factor_call;
+use crate::factor::check_levels;
+use crate::factor::is_case_of;
+use crate::matrix::matrix_call;
+use crate::index_solver::solve;
+use crate::condition::conditions;
+use crate::condition::check_conditions;
+use crate::row::has_row;
+use crate::row::keeps_rows;
+use crate::row::row_bindings;
+use crate::row::tuple_row;
+use crate::index_solver::is_index_expression;
 
 fn execute_r_function(function_code: &str) -> Result<String, Box<dyn Error>> {
     // Créer un script R temporaire avec la fonction à exécuter
@@ -95,9 +123,16 @@ fn install_header(name: &str, context: &Context) -> Context {
 pub fn eval(context: &Context, expr: &Lang) -> Context {
     match expr {
         Lang::Sequence(exprs, _h) 
-            => exprs.iter().fold(context.clone(), |ctx, expr| eval(&ctx, expr)),
+            => exprs.iter().fold(context.clone().declare_aliases(exprs), |ctx, expr| eval(&ctx, expr)),
         Lang::Let(name, ty, exp, _h) => {
-            let expr_ty = exp.typing(&context.deep_clone()).0;
+            // the top level of the file is the module of its own opaque types
+            let scope = context.open_module(&name.get_path());
+            let expr_ty = exp.typing(&scope.deep_clone()).0;
+            if let Lang::Function(_, params, _, ret_ty, _, _) = &**exp {
+                if ty.is_empty() && needs_inference(params, ret_ty) {
+                    context.warn(TypeWarning::InferredSignature(name.get_name(), expr_ty.clone(), name.get_help_data()))
+                }
+            }
             if ty.is_empty() {
                 let res = if exp.is_function() && (exp.nb_params() > 0) {
                     let first_param = expr_ty.to_function_type()
@@ -106,7 +141,7 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
                     let new_name = name.to_owned().set_type(first_param, context);
                     let res = context.to_owned()
                             .push_var_type(new_name, expr_ty.to_owned(), context)
-                            .add_generic_function(&[build_generic_function(&name.get_name())]);
+                            .add_generic_function(&[build_generic_function(name)]);
                             // TODO: check for already existing generic function upthere
                     res
                 } else if exp.is_r_function() {
@@ -118,9 +153,11 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
                     context.to_owned()
                             .push_var_type(new_name, expr_ty.to_owned(), context)
                 };
-                res
+                res.push_parameters(&name.get_name(), exp.get_parameters())
             } else {
-                let new_context = expr_ty.is_subtype(&ty, context).then(|| {
+                check_levels(context, exp, ty);
+                check_opacity(&scope, ty, &expr_ty, &exp.get_help_data());
+                let new_context = expr_ty.is_subtype(&ty, &scope).then(|| {
                     if !ty.is_any() {
                         context.to_owned()
                             .push_var_type(name.to_owned().into(), ty.to_owned(), context)
@@ -128,14 +165,29 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
                         context.to_owned()
                             .push_var_type(name.to_owned().into(), expr_ty.to_owned(), context)
                     }
-                }).expect(&TypeError::Let(ty.clone(), expr_ty).display());
+                }).unwrap_or_else(|| panic!("{}", TypeError::Let(ty.clone(), expr_ty).display()));
                 if exp.is_function() && !exp.is_undefined() {
-                    new_context.add_generic_function(&[build_generic_function(&name.get_name())])
+                    new_context.add_generic_function(&[build_generic_function(name)])
+                        .push_parameters(&name.get_name(), exp.get_parameters())
                 } else {
                     new_context
                 }
             }
         },
+        Lang::Destructure(pattern, ty, exp, _h) => {
+            let expr_ty = exp.typing(&context.deep_clone()).0;
+            let expr_ty = if ty.is_empty() {
+                expr_ty
+            } else if expr_ty.is_subtype(ty, context) {
+                ty.clone()
+            } else {
+                panic!("{}", TypeError::Let(ty.clone(), expr_ty).display())
+            };
+            pattern.bindings(&expr_ty, context).iter()
+                .fold(context.clone(), |ctx, (var, typ)| {
+                    ctx.push_var_type(var.clone().set_type(typ.clone(), context), typ.clone(), context)
+                })
+        },
         Lang::Alias(name, params, typ, h) => {
             let var = name.clone()
                 .set_type(Type::Params(params.to_vec(), h.clone()), context);
@@ -147,35 +199,34 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
             new_context3.push_alias(name.get_name(), typ.to_owned())
         },
         Lang::Assign(var, expr, _h) => {
-            let variable_assigned = Var::try_from(var.clone()).unwrap();
             let expr_type = typing(&context, expr).0;
             let expr_type_reduced = reduce_type(context, &expr_type);
-            if !context.we_check_mutability() {
-               variable_assigned.exist(context) 
-                   .map(|var| {
-                        let res = is_matching(context, &expr_type_reduced, &var.get_type())
-                            .then_some(context.clone()
-                                       .update_variable(var.clone()
-                                        .set_type(expr_type_reduced.clone(), context)));
-                        match res {
-                            Some(val) => val,
-                            None => panic!("{}", TypeError::Param(var.get_type(), expr_type_reduced.clone()).display())
-                        }
-                   })
-                   .unwrap_or(context.clone().push_var_type(
-                                        variable_assigned.set_type(expr_type_reduced.clone(), context),
-                                        expr_type_reduced, &context))
-            } else {
-            println!("We don't check");
-                let variable = context.get_true_variable(&variable_assigned);
-                let var_type = context.get_type_from_existing_variable(variable.clone());
-                let var_type_reduced = reduce_type(context, &var_type);
-                if (expr_type_reduced != var_type_reduced) && !expr_type_reduced.is_subtype(&var_type_reduced, context) {
-                    panic!("{}", TypeError::Param(expr_type, var_type).display());
-                } else if !variable.is_mutable() && context.we_check_mutability() {
-                    panic!("{}", TypeError::ImmutableVariable(variable_assigned, variable).display());
-                } else {
+            check_mutability(context, var);
+            match &**var {
+                // p$x <- value keeps the type of the field
+                Lang::Chain(_, _, _) => {
+                    let field_type = reduce_type(context, &typing(context, var).0);
+                    if !expr_type_reduced.is_subtype(&field_type, context) {
+                        panic!("{}", TypeError::Param(field_type, expr_type_reduced).display())
+                    }
                     context.clone()
+                },
+                _ => {
+                    let variable_assigned = Var::try_from(var.clone()).unwrap();
+                    variable_assigned.exist(context) 
+                       .map(|var| {
+                            let res = is_matching(context, &expr_type_reduced, &var.get_type())
+                                .then_some(context.clone()
+                                           .update_variable(var.clone()
+                                            .set_type(expr_type_reduced.clone(), context)));
+                            match res {
+                                Some(val) => val,
+                                None => panic!("{}", TypeError::Param(var.get_type(), expr_type_reduced.clone()).display())
+                            }
+                       })
+                       .unwrap_or(context.clone().push_var_type(
+                                            variable_assigned.set_type(expr_type_reduced.clone(), context),
+                                            expr_type_reduced, context))
                 }
             }
         }
@@ -188,12 +239,28 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
         },
         Lang::ModuleDecl(_name, _h) 
             => context.clone().add_module_declarations(&[expr.clone()]),
-        Lang::Signature(var, typ, _h) => {
+        Lang::Impl(interface, typ, methods, h) => {
+            let required = match interface.reduce(context) {
+                Type::Interface(args, _) => args,
+                _ => panic!("{}", TypeError::NotAnInterface(interface.clone()).display())
+            };
+            if context.get_implementation(interface, typ).is_some() {
+                panic!("{}", TypeError::ConflictingImpl(interface.clone(), typ.clone(), h.clone()).display())
+            }
+            check_methods(context, interface, typ, &required, methods, h);
+            methods.iter()
+                .fold(context.clone().push_implementation(interface, typ), |ctx, method| eval(&ctx, method))
+        },
+        Lang::Signature(var, typ, params, _h) => {
             if var.is_variable(){
+                if let Type::Function(_, types, _, _) = typ {
+                    check_defaults(context, &params.0, types, &params.get_defaults());
+                }
                 let new_var = FunctionType::try_from(typ.clone())
                             .map(|ft| var.clone().set_type(ft.get_first_param().unwrap_or(builder::empty_type()), context))
                             .unwrap_or(var.clone());
                 context.clone().push_var_type(new_var, typ.to_owned(), context)
+                    .push_parameters(&var.get_name(), params.clone())
             } else { // is alias
                 context.clone()
                     .push_var_type(var.to_owned(), typ.to_owned(), context)
@@ -203,7 +270,189 @@ pub fn eval(context: &Context, expr: &Lang) -> Context {
     }
 }
 
-fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
+fn replace_self(typ: &Type, generic: &Type) -> Type {
+    match typ {
+        Type::Alias(name, _, _, _, _) if name == "Self" => generic.clone(),
+        Type::Function(kinds, args, ret, h) => Type::Function(kinds.clone(),
+            args.iter().map(|arg| replace_self(arg, generic)).collect(),
+            Box::new(replace_self(ret, generic)), h.clone()),
+        _ => typ.clone()
+    }
+}
+
+fn same_signature(context: &Context, found: &Type, expected: &Type) -> bool {
+    match (found, expected) {
+        (Type::Function(_, args1, ret1, _), Type::Function(_, args2, ret2, _))
+            => args1.len() == args2.len()
+                && args1.iter().chain([&**ret1])
+                    .zip(args2.iter().chain([&**ret2]))
+                    .all(|(typ1, typ2)| is_matching(context, typ1, typ2)),
+        _ => false
+    }
+}
+
+// an impl block gives each method of the interface, with Self as the implementing type
+fn check_methods(context: &Context, interface: &Type, typ: &Type, required: &[ArgumentType], methods: &[Lang], h: &HelpData) {
+    let names = required.iter().map(ArgumentType::get_argument_str).collect::<Vec<_>>();
+    if let Some(var) = methods.iter()
+        .filter_map(|method| match method {
+            Lang::Let(var, _, _, _) => Some(var),
+            _ => None
+        })
+        .find(|var| !names.contains(&var.get_name())) {
+        panic!("{}", TypeError::UnknownMethod(var.get_name(), interface.clone(), var.get_help_data()).display())
+    }
+    for arg_typ in required {
+        let name = arg_typ.get_argument_str();
+        let body = methods.iter()
+            .find_map(|method| match method {
+                Lang::Let(var, _, body, _) if var.get_name() == name => Some(body),
+                _ => None
+            })
+            .unwrap_or_else(|| panic!("{}", TypeError::MissingMethod(name.clone(), interface.clone(), typ.clone(), h.clone()).display()));
+        let expected = replace_self(&arg_typ.get_type(), typ);
+        let found = typing(context, body).0;
+        if !same_signature(context, &found, &expected) {
+            panic!("{}", TypeError::WrongMethod(name, expected, found).display())
+        }
+    }
+}
+
+// in fn<T: Addable>, the functions of Addable can be called on T
+fn bound_functions(context: &Context, kinds: &[ArgumentKind]) -> Context {
+    kinds.iter()
+        .flat_map(|kind| match kind.get_bound().map(|bound| bound.reduce(context)) {
+            Some(Type::Interface(args, _)) => args.iter()
+                .map(|arg_typ| (kind.get_argument(), arg_typ.get_argument_str(), arg_typ.get_type()))
+                .collect(),
+            _ => vec![]
+        })
+        .fold(context.clone(), |cont, (generic, name, fn_typ)| {
+            let var = Var::from_name(&name).set_type(generic.clone(), &cont);
+            cont.clone().push_var_type(var, replace_self(&fn_typ, &generic), &cont)
+        })
+}
+
+fn is_interface_alias(context: &Context, typ: &Type) -> bool {
+    matches!(typ, Type::Alias(_, params, _, _, _) if params.is_empty())
+        && matches!(typ.reduce(context), Type::Interface(_, _))
+}
+
+fn has_interface_types(context: &Context, params: &[ArgumentType], ret_ty: &Type) -> bool {
+    params.iter()
+        .map(ArgumentType::get_type)
+        .chain([ret_ty.clone()])
+        .any(|typ| is_interface_alias(context, &typ))
+}
+
+// fn(a: Addable, b: Addable): Addable is read as fn<Addable: Addable>(a: Addable, b: Addable): Addable
+// so the concrete type given at the call site isn't erased
+fn interfaces_as_generics(context: &Context, kinds: &[ArgumentKind], params: &[ArgumentType], ret_ty: &Type) -> (Vec<ArgumentKind>, Vec<ArgumentType>, Type) {
+    let as_generic = |typ: &Type| match typ {
+        Type::Alias(name, _, _, _, h) if is_interface_alias(context, typ)
+            => Type::Generic(name.clone(), h.clone()),
+        _ => typ.clone()
+    };
+    let new_params = params.iter()
+        .map(|arg_typ| arg_typ.clone().set_type(as_generic(&arg_typ.get_type())))
+        .collect::<Vec<_>>();
+    let new_kinds = params.iter()
+        .map(ArgumentType::get_type)
+        .chain([ret_ty.clone()])
+        .filter(|typ| is_interface_alias(context, typ))
+        .map(|typ| ArgumentKind::from((as_generic(&typ), Kind::Type)).set_bound(Some(typ)))
+        .fold(kinds.to_vec(), |acc, kind| {
+            if acc.contains(&kind) { acc } else { acc.into_iter().chain([kind]).collect() }
+        });
+    (new_kinds, new_params, as_generic(ret_ty))
+}
+
+// why a signature can't take the arguments of a call
+fn overload_mismatch(context: &Context, signature: &Type, arg_types: &[Type]) -> Option<String> {
+    let params = signature.to_function_type().unwrap().get_param_types();
+    if params.len() != arg_types.len() {
+        return Some(format!("it takes {} arguments instead of {}", params.len(), arg_types.len()))
+    }
+    arg_types.iter().zip(params.iter())
+        .position(|(arg_typ, param)| !is_matching(context, arg_typ, param))
+        .map(|i| format!("the argument {} is {} instead of {}", i + 1, arg_types[i].pretty(), params[i].pretty()))
+}
+
+fn more_specific(context: &Context, signature1: &Type, signature2: &Type) -> bool {
+    let params1 = signature1.to_function_type().unwrap().get_param_types();
+    let params2 = signature2.to_function_type().unwrap().get_param_types();
+    params1.iter().zip(params2.iter())
+        .all(|(param1, param2)| param1.is_subtype(param2, context))
+}
+
+// add(1, 2) with @add: (int, int) -> int and @add: (num, num) -> num takes the most specific matching signature
+fn resolve_overload(context: &Context, var: &Var, values: &[Lang], arg_types: &[Type], h: &HelpData) -> Option<FunctionType> {
+    let signatures = context.get_signatures(var);
+    // a lambda gets its parameter types from the signature, so it can't choose one
+    if signatures.len() < 2 || values.iter().any(|val| matches!(val, Lang::Lambda(..))) {
+        return None
+    }
+    let arg_types = arg_types.iter()
+        .cloned()
+        .map(|typ| if propagates_na(&var.get_name()) { non_null(typ) } else { typ })
+        .collect::<Vec<_>>();
+    let (rejected, matching): (Vec<_>, Vec<_>) = signatures.iter()
+        .map(|signature| (signature.clone(), overload_mismatch(context, signature, &arg_types)))
+        .partition(|(_, reason)| reason.is_some());
+    if matching.is_empty() {
+        let reasons = rejected.into_iter()
+            .map(|(signature, reason)| (signature, reason.unwrap()))
+            .collect();
+        panic!("{}", TypeError::NoMatchingOverload(var.get_name(), reasons, h.clone()).display())
+    }
+    let candidates = matching.into_iter().map(|(signature, _)| signature).collect::<Vec<_>>();
+    candidates.iter()
+        .find(|signature1| candidates.iter().all(|signature2| more_specific(context, signature1, signature2)))
+        .unwrap_or_else(|| panic!("{}", TypeError::AmbiguousCall(var.get_name(), candidates.clone(), h.clone()).display()))
+        .to_function_type()
+}
+
+// f(x) without an f for the type of x: the error points at the call,
+// the failing stage of a pipeline
+fn check_dispatch(context: &Context, fn_var_name: &Lang, arg_types: &[Type], h: &HelpData) {
+    let var = fn_var_name.infer_var_name(arg_types.first().cloned(), context);
+    let dispatched = context.variables().any(|(var2, _)| var.match_with(var2, context));
+    let expected = context.get_signatures(&var).first()
+        .and_then(|typ| typ.to_function_type())
+        .and_then(|func| func.get_param_types().first().cloned());
+    if let (false, Some(expected), Some(received)) = (dispatched, expected, arg_types.first()) {
+        panic!("{}", TypeError::Param(expected, received.clone().set_help_data(h.clone())).display())
+    }
+}
+
+// the type given to a bounded generic must implement its interface
+fn check_generic_bounds(context: &Context, func: &FunctionType, unification_map: &UnificationMap, h: &HelpData) {
+    func.0.iter()
+        .filter_map(|kind| kind.get_bound().map(|bound| (unification_map.type_substitution(&kind.get_argument()), bound)))
+        .filter(|(concrete, _)| !matches!(concrete, Type::Generic(_, _)))
+        .for_each(|(concrete, bound)| {
+            if !concrete.reduce(context).is_subtype(&bound.reduce(context), context) {
+                panic!("{}", TypeError::UnsatisfiedBound(concrete, bound, h.clone()).display())
+            }
+        })
+}
+
+// the default value of a parameter must be of the parameter's type
+fn check_defaults(context: &Context, names: &[String], types: &[Type], defaults: &[ArgumentValue]) {
+    defaults.iter().for_each(|arg_val| {
+        let expected = names.iter()
+            .position(|name| *name == arg_val.get_argument())
+            .map(|i| types[i].reduce(context))
+            .unwrap_or_else(|| panic!("{}",
+                TypeError::UnknownArgument(arg_val.get_argument(), arg_val.get_value().get_help_data()).display()));
+        let received = typing(context, &arg_val.get_value()).0;
+        if !received.is_subtype(&expected, context) {
+            panic!("{}", TypeError::WrongDefault(arg_val.get_argument(), expected, received).display())
+        }
+    })
+}
+
+pub fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
         match (type1, type2) {
             (_, Type::Any(_)) => Some(vec![]),
             (Type::Integer(i, _), Type::Integer(j, _)) => {
@@ -218,6 +467,15 @@ fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
             },
             (_, Type::Generic(_, _)) | (_, Type::IndexGen(_, _)) | (_, Type::LabelGen(_, _))
                 => Some(vec![(type1.clone(), type2.clone())]),
+            (Type::Nullable(typ1, _), Type::Nullable(typ2, _)) => get_gen_type(typ1, typ2),
+            (Type::Empty(_), Type::Nullable(_, _)) => Some(vec![]),
+            (_, Type::Nullable(typ2, _)) => get_gen_type(type1, typ2),
+            (Type::Char(Tchar::Val(level), _), Type::Factor(levels, _))
+                => levels.contains(level).then(Vec::new),
+            (Type::Factor(levels1, _), Type::Factor(levels2, _))
+                => levels1.iter().all(|level| levels2.contains(level)).then(Vec::new),
+            (Type::Formula(vars1, _), Type::Formula(vars2, _))
+                => (vars2.is_empty() || vars1.iter().all(|var| vars2.contains(var))).then(Vec::new),
             (Type::Function(_, args1, ret_typ1, _), Type::Function(_, args2, ret_typ2, _)) => {
                 let res = args1.iter()
                     .zip(args2.iter())
@@ -227,7 +485,9 @@ fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
                     .collect::<Vec<_>>();
                 Some(res)
             }
-            (Type::Array(ind1, typ1, _), Type::Array(ind2, typ2, _)) => {
+            (Type::DataFrame(_, schema, _), Type::DataFrame(_, _, _)) if schema.is_any() => Some(vec![]),
+            (Type::Array(ind1, typ1, _), Type::Array(ind2, typ2, _))
+                | (Type::DataFrame(ind1, typ1, _), Type::DataFrame(ind2, typ2, _)) => {
                let gen1 = get_gen_type(ind1, ind2);
                let gen2 = get_gen_type(typ1, typ2);
                 match (gen1, gen2) {
@@ -236,6 +496,24 @@ fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
                         => Some(g1.iter().chain(g2.iter()).cloned().collect())
                 }
             },
+            // each member binds with the member of the pattern it matches
+            (Type::Union(types1, _), Type::Union(types2, _)) => {
+                types1.iter()
+                    .map(|typ1| types2.iter().find_map(|typ2| get_gen_type(typ1, typ2)))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|bindings| bindings.concat())
+            },
+            // a recursive type binds its parameters
+            (Type::Alias(name1, params1, path1, _, _), Type::Alias(name2, params2, path2, _, _))
+                if name1 == name2 && path1 == path2 && params1.len() == params2.len() => {
+                params1.iter().zip(params2.iter())
+                    .map(|(typ1, typ2)| get_gen_type(typ1, typ2))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|bindings| bindings.concat())
+            },
+            (Type::Record(v1, _), Type::Record(v2, _)) if has_row(v2) => row_bindings(v1, v2),
+            (Type::Tuple(_, _), Type::Tuple(types, _)) if tuple_row(types).is_some()
+                => Some(vec![(type1.clone(), tuple_row(types).unwrap())]),
             (Type::Record(v1, _), Type::Record(v2, _)) => {
                    let res = v1.iter() 
                        .zip(v2.iter())
@@ -257,17 +535,51 @@ fn get_gen_type(type1: &Type, type2: &Type) -> Option<Vec<(Type, Type)>> {
                 (Type::Tag(_name1, typ1, _h1), Type::Tag(_name2, typ2, _h2)) => {
                     get_gen_type(typ1, typ2)
                 }
+            (_, Type::Add(_, _, _) | Type::Minus(_, _, _) | Type::Mul(_, _, _) | Type::Div(_, _, _))
+                if is_index_expression(type2) => solve(type2, type1),
             (t1, t2) if t1 == t2 => Some(vec![]),
             _ => None
         }
 }
 
+// only the functions of its module can build an opaque type
+fn check_opacity(context: &Context, expected: &Type, received: &Type, h: &HelpData) {
+    if let opaque @ Type::Alias(_, _, _, true, _) = reduce_type(context, expected) {
+        if !received.is_subtype(&opaque, context) {
+            panic!("{}", TypeError::OpaqueConstruction(expected.clone(), received.clone(), h.clone()).display())
+        }
+    }
+}
+
+// only a variable declared with 'let mut' (or the fields of its record) can be assigned
+fn check_mutability(context: &Context, target: &Lang) {
+    match target {
+        Lang::Chain(_, record, _) => check_mutability(context, record),
+        lang => {
+            let declared = Var::try_from(lang.clone()).ok()
+                .and_then(|var| var.exist(context).map(|declared| (var, declared)));
+            if let Some((assigned, declared)) = declared {
+                if context.we_check_mutability() && !declared.is_mutable() {
+                    panic!("{}", TypeError::ImmutableVariable(assigned, declared).display())
+                }
+            }
+        }
+    }
+}
+
 pub fn match_types(ctx: &Context, type1: &Type, type2: &Type) 
     -> Option<Vec<(Type, Type)>> {
+    let conditions = conditions(type2);
+    check_opacity(ctx, type2, type1, &type1.get_help_data());
     let type1 = reduce_type(ctx, type1);
     let type2 = reduce_type(ctx, type2);
     let res = get_gen_type(&type1, &type2)
-        .expect(&TypeError::Param(type2, type1).display());
+        .unwrap_or_else(|| match &type1 {
+            Type::Nullable(typ1, _) if get_gen_type(typ1, &type2).is_some()
+                => panic!("{}", TypeError::NullableArgument(type1.clone(), type2.clone()).display()),
+            _ => panic!("{}", TypeError::Param(type2.clone(), type1.clone()).display())
+        });
+    check_conditions(&conditions, &res, &type1);
     let unif_map = res.iter()
         .flat_map(|(arg, par)| unification::unify(ctx, &arg, &par))
         .collect::<Vec<_>>();
@@ -292,22 +604,199 @@ fn get_variable_type(lang: &Lang, tags: &[Tag]) -> Option<(Var, Type)> {
 
 
 
+/// Cases a match expression must cover to be exhaustive
+pub fn match_cases(context: &Context, typ: &Type) -> Vec<Type> {
+    let mut cases: Vec<Type> = match typ {
+        Type::Union(types, _) => types.iter()
+            .flat_map(|t| match_cases(context, &reduce_type(context, t)))
+            .collect(),
+        Type::StrictUnion(tags, _) => tags.iter().map(Tag::to_type).collect(),
+        Type::Nullable(t, h) => match_cases(context, &reduce_type(context, t)).into_iter()
+            .chain([Type::Empty(h.clone())])
+            .collect(),
+        Type::Factor(levels, h) => levels.iter()
+            .map(|level| Type::Factor(vec![level.clone()], h.clone()))
+            .collect(),
+        t => vec![t.clone()]
+    };
+    cases.sort_by_key(|t| t.pretty());
+    cases.dedup();
+    cases
+}
+
+// the length is only computed when all its terms are known integers
+fn is_known_length(len: &Type) -> bool {
+    match len {
+        Type::Integer(Tint::Val(_), _) => true,
+        Type::Add(a, b, _) | Type::Minus(a, b, _) | Type::Mul(a, b, _) | Type::Div(a, b, _)
+            => is_known_length(a) && is_known_length(b),
+        _ => false
+    }
+}
+
+fn computed_length(len: Type) -> Type {
+    if is_known_length(&len) {
+        len.index_calculation()
+    } else {
+        builder::integer_type_default()
+    }
+}
+
+// an array of unknown length (int) has an Index of 0 and isn't checked
+fn check_bounds(position: i32, array_ty: &Type, len: &Type, h: &HelpData) {
+    let size = Index::from_type(len)
+        .map(|n| n.get_value())
+        .unwrap_or(0);
+    if size > 0 && position as u32 > size {
+        panic!("{}", TypeError::IndexOutOfBounds(h.clone(), array_ty.clone(), position).display())
+    }
+}
+
+/// Type of array[index] where the array is of type [len, elem_ty]
+fn indexing_type(context: &Context, index: &Lang, array_ty: &Type, len: &Type, elem_ty: &Type, h: &HelpData) -> Type {
+    match index {
+        // 2:5 is seq(2, 5, 1) and keeps (5 - 2) / 1 + 1 elements
+        Lang::FunctionApp(name, values, _, _) 
+            if Var::try_from(name.clone()).map(|var| var.get_name() == "seq").unwrap_or(false)
+                && values.len() == 3 => {
+            let bounds = values.iter()
+                .map(|val| reduce_type(context, &typing(context, val).0))
+                .collect::<Vec<_>>();
+            if let Type::Integer(Tint::Val(to), _) = bounds[1] {
+                check_bounds(to, array_ty, len, h)
+            }
+            let new_len = Type::Add(
+                Box::new(Type::Div(
+                    Box::new(Type::Minus(Box::new(bounds[1].clone()), Box::new(bounds[0].clone()), h.clone())),
+                    Box::new(bounds[2].clone()), h.clone())),
+                Box::new(builder::integer_type(1)), h.clone());
+            Type::Array(Box::new(computed_length(new_len)), Box::new(elem_ty.clone()), h.clone())
+        },
+        _ => match reduce_type(context, &typing(context, index).0) {
+            // xs[-2] drops the second element
+            Type::Integer(Tint::Val(i), _) if i < 0 => {
+                check_bounds(-i, array_ty, len, h);
+                let new_len = Type::Minus(Box::new(len.clone()), Box::new(builder::integer_type(1)), h.clone());
+                Type::Array(Box::new(computed_length(new_len)), Box::new(elem_ty.clone()), h.clone())
+            },
+            Type::Integer(Tint::Val(i), _) => {
+                check_bounds(i, array_ty, len, h);
+                elem_ty.clone()
+            },
+            Type::Integer(_, _) => elem_ty.clone(),
+            // the number (and the values) of the selected elements are only known at runtime
+            Type::Boolean(_) 
+                => Type::Array(Box::new(builder::integer_type_default()), Box::new(elem_ty.clone().generalize()), h.clone()),
+            Type::Array(_, inner, _) if matches!(*inner, Type::Boolean(_)) 
+                => Type::Array(Box::new(builder::integer_type_default()), Box::new(elem_ty.clone().generalize()), h.clone()),
+            Type::Array(n, inner, _) if matches!(*inner, Type::Integer(_, _)) 
+                => Type::Array(n, Box::new(elem_ty.clone().generalize()), h.clone()),
+            typ => panic!("{}", TypeError::WrongIndex(index.get_help_data(), typ).display())
+        }
+    }
+}
+
+/// Type of list[[index]] for a computed index: one element of an array,
+/// any of the values of a tuple or a record
+fn list_indexing_type(context: &Context, index: &Lang, list_ty: &Type, h: &HelpData) -> Type {
+    let index_ty = reduce_type(context, &typing(context, index).0);
+    let values = match (list_ty, &index_ty) {
+        (Type::Array(len, elem_ty, _), Type::Integer(i, _)) => {
+            if let Tint::Val(i) = i { check_bounds(*i, list_ty, len, h) }
+            vec![(**elem_ty).clone()]
+        },
+        (Type::Tuple(vals, _), Type::Integer(_, _)) => vals.clone(),
+        (Type::Record(fields, _), Type::Char(_, _)) => fields.iter().map(ArgumentType::get_type).collect(),
+        _ => panic!("{}", TypeError::WrongIndex(index.get_help_data(), index_ty).display())
+    };
+    let types = values.into_iter().collect::<HashSet<_>>();
+    if types.len() == 1 {
+        types.into_iter().next().unwrap()
+    } else { Type::Union(types, h.clone()) }
+}
+
+// lm(formula(y ~ x), data): the variables of a formula must be columns of the data
+fn check_formula_columns(context: &Context, values: &[Lang], arg_types: &[Type]) {
+    if !values.iter().any(|value| matches!(value, Lang::Formula(..))) {
+        return
+    }
+    let types = arg_types.iter()
+        .map(|typ| reduce_type(context, typ))
+        .collect::<Vec<_>>();
+    let data = types.iter()
+        .find_map(|typ| match typ {
+            Type::Record(fields, _) => Some((typ, fields.clone())),
+            Type::DataFrame(_, schema, _) => match &**schema {
+                Type::Record(fields, _) => Some((typ, fields.clone())),
+                _ => None
+            },
+            _ => None
+        });
+    if let Some((data_type, fields)) = data {
+        let missing = types.iter()
+            .filter_map(|typ| match typ {
+                Type::Formula(variables, h) => Some((variables, h)),
+                _ => None
+            })
+            .flat_map(|(variables, h)| variables.iter().map(move |var| (var, h)))
+            .find(|(var, _)| !fields.iter().any(|arg_typ| &arg_typ.get_argument_str() == *var));
+        if let Some((var, h)) = missing {
+            panic!("{}", TypeError::FieldNotFound((var.clone(), h.clone()), data_type.clone()).display())
+        }
+    }
+}
+
+/// Type of expr where a value of type expected is awaited.
+/// The untyped parameters of a lambda are taken from the expected function type
+pub fn typing_with_expected(context: &Context, expr: &Lang, expected: &Type) -> (Type, Context) {
+    match (expr, reduce_type(context, expected)) {
+        (Lang::Lambda(params, body, h), Type::Function(_, types, _, _))
+            => lambda_typing(context, params, body, &types, h),
+        _ => typing(context, expr)
+    }
+}
+
+fn lambda_typing(context: &Context, params: &[ArgumentType], body: &Lang, expected: &[Type], h: &HelpData) -> (Type, Context) {
+    let list_of_types = params.iter().enumerate()
+        .map(|(i, arg_typ)| match arg_typ.get_type() {
+            Type::Empty(_) => expected.get(i).cloned().unwrap_or(builder::any_type()),
+            typ => typ
+        }).collect::<Vec<_>>();
+    let sub_context = params.iter()
+        .zip(list_of_types.iter())
+        .map(|(arg_typ, typ)| (arg_typ.clone().set_type(typ.clone()).to_var(context), typ.reduce(context)))
+        .fold(context.clone(), |cont, (var, typ)| cont.clone().push_var_type(var, typ, &cont));
+    let body_type = body.typing(&sub_context).0.reduce(&sub_context);
+    (Type::Function(vec![], list_of_types, Box::new(body_type), h.clone()), context.clone())
+}
+
 pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
     match expr {
         Lang::Number(_, h) => (Type::Number(h.clone()), context.clone()),
         Lang::Integer(i, h) => (Type::Integer((*i).into(), h.clone()), context.clone()),
         Lang::Bool(_, h) => (Type::Boolean(h.clone()), context.clone()),
         Lang::Char(s, h) => (Type::Char(s.to_owned().into(), h.clone()), context.clone()),
-        Lang::Empty(h) => (Type::Empty(h.clone()), context.clone()),
+        Lang::Na(h) | Lang::Empty(h) => (Type::Empty(h.clone()), context.clone()),
         Lang::And(e1, e2, _) | Lang::Or(e1, e2, _) => {
             (typing(context, e1).0.is_boolean() && typing(context, e2).0.is_boolean())
                 .then_some((builder::boolean_type(), context.clone()))
                 .expect("Type error")
         }
         Lang::Eq(e1, e2, _) | Lang::LesserOrEqual(e1, e2, _) | Lang::GreaterOrEqual(e1, e2, _) | Lang::GreaterThan(e1, e2, _) | Lang::LesserThan(e1, e2, _) => {
-            (typing(context, e1).0 == typing(context, e2).0)
-                .then_some((builder::boolean_type(), context.clone()))
-                .expect("Type error")
+            let ty1 = reduce_type(context, &typing(context, e1).0).generalize();
+            let ty2 = reduce_type(context, &typing(context, e2).0).generalize();
+            // comparing with NA gives NA
+            let with_na = is_nullable(&ty1) || is_nullable(&ty2);
+            let typ = match (non_null(ty1), non_null(ty2)) {
+                // xs > 0 compares each element and gives a logical mask
+                (Type::Array(len, elem_ty, h), ty) | (ty, Type::Array(len, elem_ty, h)) 
+                    if elem_ty.clone().generalize() == ty 
+                    => Type::Array(len, Box::new(builder::boolean_type()), h),
+                (ty1, ty2) => (ty1 == ty2)
+                    .then_some(builder::boolean_type())
+                    .expect("Type error")
+            };
+            if with_na { (nullable(typ), context.clone()) } else { (typ, context.clone()) }
         }
         Lang::Chain(e1, e2, _) => {
             let ty2 = typing(context, e2).0;
@@ -324,6 +813,16 @@ pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
                         .map(|arg_typ| (arg_typ.1.clone(), context.clone()))
                         .expect("Field not found")
                 },
+                (Type::Array(_, elem_ty, _), Lang::Integer(_, _)) => {
+                    (*elem_ty, context.clone())
+                },
+                (Type::DataFrame(_, schema, h), Lang::Variable(_, _, _, _, _, _) | Lang::Char(_, _)) if schema.is_any() 
+                    => (Type::Any(h), context.clone()),
+                (Type::DataFrame(rows, schema, h2), Lang::Variable(name, _, _, _, _, h) | Lang::Char(name, h)) => {
+                    let column = column_type(&rows, &schema, &name)
+                        .unwrap_or_else(|| panic!("{}", TypeError::FieldNotFound((name.clone(), h), Type::DataFrame(rows, schema, h2)).display()));
+                    (column, context.clone())
+                },
                 (Type::Tuple(vals, _), Lang::Integer(i, _)) => {
                     vals.iter()
                         .nth((i-1) as usize)
@@ -336,21 +835,40 @@ pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
                         .map(replace_fields_type_if_needed(context, at)).collect::<Vec<_>>();
                     (Type::Record(fields3, h.clone()), context.clone())
                 },
+                (opaque @ Type::Alias(_, _, _, true, _), lang)
+                    => panic!("{}", TypeError::OpaqueAccess(opaque, lang.into()).display()),
                 (a, b) => panic!("Type error we can't combine {} and {:?}", a, b)
             }
         },
-        Lang::Function(kinds, params, ret_ty, body, h) => {
+        Lang::Function(kinds, params, defaults, ret_ty, body, h) if needs_inference(params, ret_ty) => {
+            let (new_params, new_ret) = infer_function(context, params, defaults, ret_ty, body);
+            let kinds = extract_generics(&new_params, &new_ret).into_iter()
+                .map(|kind| kind.with_bound_from(kinds))
+                .collect();
+            typing(context, &Lang::Function(kinds, new_params, defaults.clone(), new_ret, body.clone(), h.clone()))
+        },
+        Lang::Function(kinds, params, defaults, ret_ty, body, h) if has_interface_types(context, params, ret_ty) => {
+            let (new_kinds, new_params, new_ret) = interfaces_as_generics(context, kinds, params, ret_ty);
+            typing(context, &Lang::Function(new_kinds, new_params, defaults.clone(), new_ret, body.clone(), h.clone()))
+        },
+        Lang::Function(kinds, params, defaults, ret_ty, body, h) => {
             let list_of_types = params.iter()
                 .map(ArgumentType::get_type)
                 .collect::<Vec<_>>();
+            let names = params.iter()
+                .map(ArgumentType::get_argument_str)
+                .collect::<Vec<_>>();
+            check_defaults(context, &names, &list_of_types, defaults);
             let sub_context = params.into_iter()
                 .map(|arg_typ| arg_typ.clone().to_var(context))
                 .zip(list_of_types.clone().into_iter().map(|typ| typ.reduce(context)))
-                .fold(context.clone(), |cont, (var, typ)| cont.clone().push_var_type(var, typ, &cont));
+                .fold(bound_functions(context, kinds), |cont, (var, typ)| cont.clone().push_var_type(var, typ, &cont));
             let res = body.typing(&sub_context);
+            check_opacity(context, ret_ty, &res.0, &body.get_help_data());
             let reduced_body_type = res.0.reduce(&sub_context);
             let reduced_expected_ty = ret_ty.reduce(&context);
-            if !reduced_body_type.is_subtype(&reduced_expected_ty, context) {
+            if !reduced_body_type.is_subtype(&reduced_expected_ty, context)
+                || !keeps_rows(&reduced_body_type, &reduced_expected_ty) {
                 None.expect(
                     &TypeError::UnmatchingReturnType(reduced_expected_ty, reduced_body_type).display())
             }
@@ -364,7 +882,7 @@ pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
             } else if exprs.len() == 0 {
                 (Type::Empty(HelpData::default()), context.clone()) 
             } else {
-                let context2 = context.clone();
+                let context2 = context.clone().declare_aliases(exprs);
                 let mut exprs2 = exprs.clone();
                 let exp = exprs2.pop().unwrap();
                 let new_context = exprs.iter()
@@ -374,32 +892,68 @@ pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
         },
         Lang::FunctionApp(fn_var_name, values, _, h) => {
             let var = Var::try_from(fn_var_name.clone()).unwrap();
-            if context.is_an_untyped_function(&var.get_name()) {
+            if let Some(typ) = data_frame_call(context, &var.get_name(), values, h)
+                .or_else(|| tidy_call(context, &var.get_name(), values, h))
+                .or_else(|| factor_call(context, &var.get_name(), values, h))
+                .or_else(|| matrix_call(context, &var.get_name(), values, h)) {
+                (typ, context.clone())
+            } else if context.is_an_untyped_function(&var.get_name()) {
                 (Type::Empty(h.clone()), context.clone())
             } else {
-                let func = fn_var_name.clone()
-                    .get_related_function(values, context)
-                    .expect(&TypeError::UndefinedFunction((**fn_var_name).clone()).display());
-                let param_types = func.get_param_types();
+                let values = &context.get_parameters(&var.get_name())
+                    .map(|params| params.arrange(values, h))
+                    .unwrap_or(values.clone());
+                // the arguments are typed once for the whole call
+                let arg_types = values.iter()
+                    .map(|value| typing(context, value).0)
+                    .collect::<Vec<_>>();
+                check_formula_columns(context, values, &arg_types);
+                let func = resolve_overload(context, &var, values, &arg_types, h)
+                    .or_else(|| {
+                        check_dispatch(context, fn_var_name, &arg_types, h);
+                        fn_var_name.clone().get_dispatched_function(arg_types.first().cloned(), context)
+                    })
+                    .unwrap_or_else(|| panic!("{}", TypeError::UndefinedFunction((**fn_var_name).clone()).display()));
+                // add(x, 1) with x: int? is an int? instead of an error
+                let na_arguments = if propagates_na(&var.get_name()) {
+                    arg_types.iter()
+                        .map(|typ| is_nullable(&typ.reduce(context)))
+                        .collect::<Vec<_>>()
+                } else { vec![] };
+                let param_types = func.get_param_types().into_iter()
+                    .enumerate()
+                    .map(|(i, typ)| if na_arguments.get(i) == Some(&true) { nullable(typ) } else { typ })
+                    .collect::<Vec<_>>();
                 let unification_map = context
-                        .get_unification_map(values, &param_types)
+                        .get_unification_map(values, &arg_types, &param_types)
                         .unwrap_or(UnificationMap::new(vec![]));
-                let (new_ret_typ, new_context) = unification_map
-                        .apply_unification_type(context, &func.get_ret_type());
+                check_generic_bounds(context, &func, &unification_map, h);
+                let (new_ret_typ, new_context) = match func.get_ret_type() {
+                    // a type guard is a boolean once called
+                    Type::Guard(_, _, h) => (builder::boolean_type().set_help_data(h), context.clone()),
+                    ret_typ if na_arguments.contains(&true) => {
+                        let (typ, cont) = unification_map.apply_unification_type(context, &ret_typ);
+                        (nullable(non_null(typ)), cont)
+                    },
+                    ret_typ => unification_map.apply_unification_type(context, &ret_typ)
+                };
                 let params = func.get_param_types().iter()
                             .map(|p| unification_map.apply_unification_type(context, p).0)
                             .collect::<Vec<_>>();
                 (new_ret_typ.clone(), new_context.push(expr.clone(), func.set_params(params).set_ret_type(new_ret_typ)))
             }
         }
+        Lang::Lambda(params, body, h) => lambda_typing(context, params, body, &[], h),
+        Lang::Formula(_, variables, h) => (Type::Formula(variables.clone(), h.clone()), context.clone()),
         Lang::Tag(name, expr, h) => {
             let ty = typing(context, expr).0;
             (Type::Tag(name.clone(), Box::new(ty), h.clone()), context.clone())
         }
         Lang::If(cond, true_branch, false_branch, _h) => {
             if typing(context, cond).0.is_boolean() {
-                let true_ty = typing(context, true_branch).0;
-                let false_ty = typing(context, false_branch).0;
+                let (true_context, false_context) = narrow(context, cond);
+                let true_ty = typing(&true_context, true_branch).0;
+                let false_ty = typing(&false_context, false_branch).0;
                 let set = if let Type::Union(v, h) = false_ty {
                     let mut set = v; set.insert(true_ty);
                     set
@@ -450,54 +1004,58 @@ pub fn typing(context: &Context, expr: &Lang) -> (Type, Context) {
                 }).collect();
             (Type::Record(field_types, h.clone()), context.clone())
         }
-        Lang::Match(exp, var, branches, _h) => {
+        Lang::Match(exp, var, branches, h) => {
             let var_ty = reduce_type(context, &typing(context, &**exp).0);
-            match var_ty {
-                Type::Union(union_types, h) => {
-                    let set = branches.iter().map(|(t, _)| t).cloned().collect::<HashSet<Type>>();
-                    if union_types != set {
-                        panic!("Some types are missing");
+            let cases = match_cases(context, &var_ty);
+            let mut remaining = cases.clone();
+            let types = branches.iter()
+                .map(|(typ, bexp)| {
+                    let covered = remaining.iter()
+                        .filter(|case| is_case_of(context, case, typ))
+                        .cloned().collect::<Vec<_>>();
+                    if covered.is_empty() {
+                        if cases.iter().any(|case| is_case_of(context, case, typ)) {
+                            context.warn(TypeWarning::DuplicateArm(typ.clone()));
+                        } else {
+                            context.warn(TypeWarning::UnreachableArm(typ.clone(), var_ty.clone()));
+                        }
                     }
-                    let types = branches.iter()
-                        .map(|(typ, bexp)| {
-                            let new_context = context.clone().push_var_type(var.clone(), typ.clone(), context);
-                            typing(&new_context, bexp).0
-                        }).collect::<HashSet<_>>();
-                    let output_type = if types.len() == 1 {
-                        types.iter().next().unwrap().clone()
-                    } else {Type::Union(types, h)};
-                    (output_type, context.clone())
-                },
-                _ => panic!("Type error"),
+                    remaining.retain(|case| !covered.contains(case));
+                    let narrowed = match covered.len() {
+                        0 => typ.clone(),
+                        1 => covered[0].clone(),
+                        _ => Type::Union(covered.into_iter().collect(), typ.get_help_data())
+                    };
+                    let new_context = context.clone().push_var_type(var.clone(), narrowed, context);
+                    typing(&new_context, bexp).0
+                }).collect::<HashSet<_>>();
+            if !remaining.is_empty() {
+                panic!("{}", TypeError::NonExhaustiveMatch(h.clone(), remaining).display());
             }
+            let output_type = if types.len() == 1 {
+                types.iter().next().unwrap().clone()
+            } else {Type::Union(types, h.clone())};
+            (output_type, context.clone())
         }
-        Lang::ArrayIndexing(expr, index, _h) => {
+        Lang::ArrayIndexing(expr, index, h) => {
             let ty = typing(context, expr).0;
-            match ty {
-                Type::Array(len, elem_ty, _) => {
-                    match Index::from_type(&(*len)) {
-                        Some(n)  => {
-                            let index2 = n.get_value();
-                            if (*index as u32) <  index2 {
-                                (*elem_ty, context.clone())
-                            } else {
-                                panic!("Index out of bounds");
-                            }
-                        },
-                        // TODO: check condition in Index::from_type() if
-                        // we get generics (#M), Any or Empty as an index for 
-                        // array type: [id, type]
-                       None => (*elem_ty, context.clone())
-                    }
-                },
+            match reduce_type(context, &ty) {
+                Type::Array(len, elem_ty, _) 
+                    => (indexing_type(context, index, &ty, &len, &elem_ty, h), context.clone()),
                 Type::Any(h) => {
                     (builder::empty_type().set_help_data(h), context.clone())
                 },
-                _ => panic!("Indexing error: {:?} can't be indexable by {}",
+                _ => panic!("Indexing error: {:?} can't be indexable by {:?}",
                         expr, 
                         index),
             }
         },
+        Lang::ListIndexing(expr, index, h) => {
+            match reduce_type(context, &typing(context, expr).0) {
+                Type::Any(h) => (builder::empty_type().set_help_data(h), context.clone()),
+                ty => (list_indexing_type(context, index, &ty, h), context.clone())
+            }
+        },
         Lang::Variable(_, _, _, _, _, _) => {
             let old_var = Var::try_from(expr.clone()).unwrap();
             let var = context.get_true_variable(&old_var);
@@ -596,6 +1154,35 @@ fn if_strict_mode(){
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::parse_exp;
+    use crate::metaprogrammation;
+    use crate::types::if_type;
+    use crate::types::ltype;
+
+    fn type_of(code: &str) -> Type {
+        let exp = parse_exp(code.into()).unwrap().1;
+        typing(&Context::default(), &exp).0
+    }
+
+    fn r_of_program(code: &str) -> String {
+        let adt = metaprogrammation(parse(code.into()).unwrap().1);
+        let context = typing(&Context::default(), &Lang::Sequence(adt.0.clone(), HelpData::default())).1;
+        let (header, chunks) = adt.to_r_program(&context);
+        chunks.into_iter().fold(header, |code, (chunk, _)| code + "\n" + &chunk)
+    }
+
+    // the warnings are shared by the contexts derived from the first one
+    fn warnings_of(code: &str) -> Vec<TypeWarning> {
+        let exp = parse_exp(code.into()).unwrap().1;
+        let context = Context::default();
+        typing(&context, &exp);
+        context.get_warnings()
+    }
+
+    fn type_of_program(code: &str) -> Type {
+        let adt = metaprogrammation(parse(code.into()).unwrap().1);
+        typing(&Context::default(), &Lang::Sequence(adt.0, HelpData::default())).0
+    }
 
     #[test]
     fn test_type_equality(){
@@ -603,4 +1190,1089 @@ mod tests {
         let b = builder::integer_type(17);
         assert!(a == b)
     }
+
+    #[test]
+    fn test_match_exhaustive_option() {
+        let typ = type_of("type Option<T> = .Some(T) | .None;
+            let o: Option<int> <- Some(3);
+            match o as x { .Some(int) => true, .None => false };");
+        assert_eq!(typ, builder::boolean_type());
+    }
+
+    #[test]
+    fn test_match_generic_pattern() {
+        let typ = type_of("type Option<T> = .Some(T) | .None;
+            let o: Option<char> <- Some(\"a\");
+            match o as x { .Some(T) => 1, .None => 2 };");
+        assert!(typ.is_subtype(&builder::integer_type_default(), &Context::default()));
+    }
+
+    #[test]
+    fn test_match_strict_union() {
+        let typ = type_of("type Color = .Red || .Green;
+            let c: Color <- Red;
+            match c as x { .Red => 1, .Green => 2 };");
+        assert!(typ.is_subtype(&builder::integer_type_default(), &Context::default()));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing `.None`")]
+    fn test_match_missing_case() {
+        type_of("type Option<T> = .Some(T) | .None;
+            let o: Option<int> <- Some(3);
+            match o as x { .Some(int) => 1, .Some(int) => 2 };");
+    }
+
+    #[test]
+    fn test_match_duplicate_arm() {
+        let warnings = warnings_of("type Option<T> = .Some(T) | .None;
+            let first <- fn(o: Option<int>): int {
+                match o as x { .Some(int) => 1, .Some(int) => 2, .None => 3 }
+            };");
+        assert!(matches!(warnings.as_slice(), [TypeWarning::DuplicateArm(_)]));
+    }
+
+    #[test]
+    fn test_match_unreachable_arm() {
+        let warnings = warnings_of("type Option<T> = .Some(T) | .None;
+            let o: Option<int> <- Some(3);
+            match o as x { .Some(int) => 1, .None => 2, .Empty => 3 };");
+        assert!(matches!(warnings.as_slice(), [TypeWarning::UnreachableArm(_, _)]));
+    }
+
+    #[test]
+    fn test_destructure_nested() {
+        let typ = type_of("let pair <- :{3, :{name: \"a\", age: true}};
+            let (a, {name, age: n}) <- pair;
+            n;");
+        assert_eq!(typ, builder::boolean_type());
+    }
+
+    #[test]
+    #[should_panic(expected = "The field 'z' doesn't exist")]
+    fn test_destructure_missing_field() {
+        type_of("let point <- :{x: 1, y: 2};
+            let {x, z} <- point;
+            x;");
+    }
+
+    #[test]
+    #[should_panic(expected = "The pattern has 3 elements")]
+    fn test_destructure_wrong_arity() {
+        type_of("let pair <- :{1, 2};
+            let (a, b, c) <- pair;
+            a;");
+    }
+
+    #[test]
+    fn test_named_arguments_reordered() {
+        let typ = type_of("let f <- fn(x: int, scale: num = 1.0): num { scale };
+            f(scale = 2.0, x = 3);");
+        assert_eq!(typ.pretty(), "num");
+    }
+
+    #[test]
+    fn test_default_argument_omitted() {
+        let typ = type_of("let f <- fn(x: int, scale: num = 1.0): int { x };
+            f(3);");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing argument 'x'")]
+    fn test_missing_argument() {
+        type_of("let f <- fn(x: int, scale: num = 1.0): num { scale };
+            f(scale = 2.0);");
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many arguments")]
+    fn test_extra_argument() {
+        type_of("let f <- fn(x: int, scale: num = 1.0): num { scale };
+            f(3, 2.0, 1.0);");
+    }
+
+    #[test]
+    #[should_panic(expected = "The default value of 'scale'")]
+    fn test_wrong_default_type() {
+        type_of("let f <- fn(x: int, scale: num = true): num { scale };
+            f(3);");
+    }
+
+    #[test]
+    fn test_signature_with_defaults() {
+        let typ = type_of("@read__csv: (file: char, header: bool = true) -> int;
+            read__csv(\"a.csv\");");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    fn test_typed_lambda() {
+        let typ = type_of("let g <- \\(a: int, b: char) b;
+            g;");
+        assert_eq!(typ.pretty(), "fn<>(int, char) -> char");
+    }
+
+    #[test]
+    fn test_lambda_params_inferred() {
+        let typ = type_of(&format!("{}\nlet v <- [true, false];\nmap(v, \\(a) a);", default_signatures(&["map"])));
+        assert_eq!(typ.pretty(), "[2, bool]");
+    }
+
+    #[test]
+    fn test_tilde_lambda_inferred() {
+        let typ = type_of(&format!("{}\nlet v <- [true, false];\nmap(v, ~ x);", default_signatures(&["map"])));
+        assert_eq!(typ.pretty(), "[2, bool]");
+    }
+
+    #[test]
+    fn test_slice_length() {
+        let typ = type_of("let xs <- [true, false, true, true, false];
+            xs[2:5];");
+        assert_eq!(typ.pretty(), "[4, bool]");
+    }
+
+    #[test]
+    fn test_negative_index() {
+        let typ = type_of("let xs <- [true, false, true];
+            xs[-1];");
+        assert_eq!(typ.pretty(), "[2, bool]");
+    }
+
+    #[test]
+    fn test_mask_index() {
+        let typ = type_of("let xs <- [1, 2, 3];
+            xs[xs > 0];");
+        assert_eq!(typ.pretty(), "[int, int]");
+    }
+
+    #[test]
+    fn test_list_index_expression() {
+        let typ = type_of("let xs <- [true, false, true];
+            let next <- fn(n: int): int { n };
+            let get <- fn(i: int): bool { xs[[next(i)]] };
+            get(1);");
+        assert_eq!(typ.pretty(), "bool");
+    }
+
+    #[test]
+    fn test_list_index_tuple() {
+        let typ = type_of("let t <- list(true, \"x\");
+            let next <- fn(n: int): int { n };
+            t[[next(1)]];");
+        match typ {
+            Type::Union(types, _) => assert_eq!(types.len(), 2),
+            typ => panic!("{} is not a union", typ.pretty())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be indexed by a value of type bool")]
+    fn test_list_index_wrong_type() {
+        type_of("let xs <- [1, 2, 3];
+            xs[[true and false]];");
+    }
+
+    #[test]
+    fn test_pipe_typed_as_application() {
+        let typ = type_of("let f <- fn(a: int, b: bool): char { \"ok\" };
+            let x <- 3;
+            x |> f(true);");
+        assert_eq!(typ.pretty(), "char");
+    }
+
+    #[test]
+    fn test_pipe_placeholder() {
+        let typ = type_of("let f <- fn(a: int, b: bool): bool { b };
+            let g <- fn(b: bool): int { 3 };
+            let x <- 3;
+            true |> f(x, _) |> g;");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    #[should_panic(expected = "type bool doesn't match type int")]
+    fn test_pipe_failing_stage() {
+        type_of("let inc <- fn(n: int): int { n };
+            let neg <- fn(b: bool): bool { b };
+            1 |> inc() |> neg() |> inc();");
+    }
+
+    #[test]
+    fn test_pipe_stage_position() {
+        let code = "1 |> inc() |> neg() |> inc()";
+        let exp = parse_exp(code.into()).unwrap().1;
+        let stage = match exp {
+            Lang::Sequence(exprs, _) => match &exprs[0] {
+                Lang::FunctionApp(_, args, _, _) => args[0].clone(),
+                lang => panic!("{:?} is not a call", lang)
+            },
+            lang => panic!("{:?} is not a call", lang)
+        };
+        assert_eq!(stage.get_help_data().get_offset(), code.find("|> neg").unwrap());
+    }
+
+    #[test]
+    fn test_pipe_generic_unification() {
+        let typ = type_of("@map: (a: [#N, T], f: (T) -> U) -> [#N, U];
+            let v <- [true, false];
+            v |> map(~ x);");
+        assert_eq!(typ.pretty(), "[2, bool]");
+    }
+
+    #[test]
+    fn test_formula_checked_against_data() {
+        let typ = type_of("@lm: (f: Formula, data: Any) -> Formula;
+            let df <- :{y: 1, x: 2, z: 3};
+            lm(formula(y ~ x + log(z)), df);");
+        assert_eq!(typ.pretty(), "Formula");
+    }
+
+    #[test]
+    fn test_formula_variables_compared() {
+        let context = Context::default();
+        let formula = |vars: &[&str]| Type::Formula(vars.iter().map(|var| var.to_string()).collect(), HelpData::default());
+        assert_ne!(formula(&["y", "x"]), formula(&["y", "z"]));
+        assert!(formula(&["y", "x"]).is_subtype(&formula(&[]), &context));
+        assert!(formula(&["y"]).is_subtype(&formula(&["y", "x"]), &context));
+        assert!(!formula(&["y", "z"]).is_subtype(&formula(&["y", "x"]), &context));
+    }
+
+    #[test]
+    #[should_panic(expected = "The field 'w' doesn't exist")]
+    fn test_formula_unknown_column() {
+        type_of("@lm: (f: Formula, data: Any) -> Any;
+            let df <- :{y: 1, x: 2};
+            lm(formula(y ~ x + log(w)), df);");
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds")]
+    fn test_index_out_of_bounds() {
+        type_of("let xs <- [true, false];
+            xs[3];");
+    }
+
+    #[test]
+    fn test_selective_import() {
+        let typ = type_of_program("module calc {
+                pub let flag <- true;
+                pub let neg <- fn(b: bool): bool { b };
+            };
+            use calc::{flag, neg};
+            neg(flag);");
+        assert_eq!(typ.pretty(), "bool");
+    }
+
+    #[test]
+    fn test_glob_import_of_reexport() {
+        let typ = type_of_program("module a {
+                pub let flag <- true;
+            };
+            module b {
+                pub use a::flag;
+            };
+            use b::*;
+            flag;");
+        assert_eq!(typ.pretty(), "bool");
+    }
+
+    #[test]
+    #[should_panic(expected = "The module calc has no item named 'nope'")]
+    fn test_unknown_import() {
+        type_of_program("module calc {
+                pub let flag <- true;
+            };
+            use calc::{nope};");
+    }
+
+    #[test]
+    #[should_panic(expected = "The module nowhere has no item named 'f'")]
+    fn test_import_from_unknown_module() {
+        type_of_program("use nowhere::{f};");
+    }
+
+    #[test]
+    fn test_bare_use() {
+        let typ = type_of_program("let flag <- true;
+            use flag;
+            flag;");
+        assert_eq!(typ.pretty(), "bool");
+    }
+
+    #[test]
+    fn test_module_function_codegen() {
+        let code = r_of_program("module auth {
+                pub let make <- fn(n: char): bool { true };
+            };
+            use auth::{make};
+            make(\"a\");");
+        assert_eq!(code.matches("auth <- new.env()").count(), 1);
+        assert!(code.contains("auth$make <- function(x, ...) {"));
+        assert!(code.contains("environment(auth$make) <- auth"));
+        assert!(code.contains("registerS3method('make', 'character', auth$make.character, envir = auth)"));
+        assert!(code.contains("make <- auth$make"));
+        assert!(!code.contains("\nmake <- function(x, ...)"));
+    }
+
+    #[test]
+    fn test_nested_module() {
+        let typ = type_of_program("module outer {
+                module inner {
+                    pub let flag <- true;
+                };
+            };
+            use outer::inner::flag;
+            flag;");
+        assert_eq!(typ.pretty(), "bool");
+    }
+
+    #[test]
+    fn test_modules_keyed_by_full_path() {
+        let dir = std::env::temp_dir().join("typr_module_paths");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a/util.ty"), "pub let f <- fn(x: int): char { \"a\" };").unwrap();
+        fs::write(dir.join("b/util.ty"), "pub let f <- fn(x: int): bool { true };").unwrap();
+        let main = dir.join("main.ty").display().to_string();
+        let code = "mod a::util;\nmod b::util;\nuse a::util::f;\nf(1);";
+        let adt = metaprogrammation(parse(LocatedSpan::new_extra(code, main)).unwrap().1);
+        let typ = typing(&Context::default(), &Lang::Sequence(adt.0, HelpData::default())).0;
+        assert_eq!(typ.pretty(), "char");
+    }
+
+    #[test]
+    #[should_panic(expected = "Import cycle: ")]
+    fn test_import_cycle() {
+        let dir = std::env::temp_dir().join("typr_import_cycle");
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(dir.join("lib/a.ty"), "mod b;").unwrap();
+        fs::write(dir.join("lib/b.ty"), "mod a;").unwrap();
+        let main = dir.join("main.ty").display().to_string();
+        let adt = parse(LocatedSpan::new_extra("mod lib::a;", main)).unwrap().1;
+        metaprogrammation(adt);
+    }
+
+    #[test]
+    fn test_infer_parameters_from_calls() {
+        let typ = type_of("let neg <- fn(b: bool): bool { b };
+            let twice <- fn(n: int): int { n };
+            fn(x, y) {
+                let z <- twice(x);
+                if (neg(y)) { z } else { twice(z) }
+            };");
+        assert_eq!(typ.pretty(), "fn<>(int, bool) -> int");
+    }
+
+    #[test]
+    fn test_infer_unused_parameter_is_generic() {
+        let typ = type_of("let twice <- fn(n: int): int { n };
+            fn(a, k = 3) { twice(k) };");
+        assert_eq!(typ.pretty(), "fn<T>(T, int) -> int");
+    }
+
+    #[test]
+    fn test_inferred_signature_diagnostic() {
+        let warnings = warnings_of("let twice <- fn(n: int): int { n };
+            let scale <- fn(a, k = 3) { twice(k) };");
+        match warnings.as_slice() {
+            [TypeWarning::InferredSignature(name, typ, _)] => {
+                assert_eq!(name, "scale");
+                assert_eq!(typ.pretty(), "fn<T>(T, int) -> int");
+            },
+            _ => panic!("expected one inferred signature, got {:?}", warnings)
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "type bool doesn't match type int")]
+    fn test_infer_conflicting_uses() {
+        type_of("let neg <- fn(b: bool): bool { b };
+            let twice <- fn(n: int): int { n };
+            fn(x) { neg(x); twice(x) };");
+    }
+
+    #[test]
+    fn test_bounded_generic_keeps_concrete_type() {
+        let typ = type_of("type Addable = interface { add: fn(a: Self, b: Self): Self };
+            let add <- fn(a: int, b: int): int { a };
+            let sum <- fn<T: Addable>(a: T, b: T): T { add(a, b) };
+            sum(1, 2);");
+        assert!(matches!(typ, Type::Integer(_, _)));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't implement Addable")]
+    fn test_unsatisfied_bound() {
+        type_of("type Addable = interface { add: fn(a: Self, b: Self): Self };
+            let add <- fn(a: int, b: int): int { a };
+            let sum <- fn<T: Addable>(a: T, b: T): T { add(a, b) };
+            sum(true, false);");
+    }
+
+    #[test]
+    fn test_interface_parameters_keep_concrete_type() {
+        let typ = type_of("type Addable = interface { add: fn(a: Self, b: Self): Self };
+            let add <- fn(a: int, b: int): int { a };
+            let sum <- fn(a: Addable, b: Addable): Addable { add(a, b) };
+            sum(1, 2);");
+        assert!(matches!(typ, Type::Integer(_, _)));
+    }
+
+    #[test]
+    fn test_narrowing_class_test() {
+        let typ = type_of("@is__character: (x: Any) -> x is char;
+            let x: int | char <- 3;
+            let name <- fn(s: char): char { s };
+            if (is__character(x)) { x } else { name(\"a\") };");
+        assert_eq!(typ.pretty(), "char");
+        // the else branch only sees the int
+        let typ = type_of("@is__character: (x: Any) -> x is char;
+            let x: int | char <- 3;
+            let len <- fn(s: char): int { 3 };
+            if (is__character(x)) { len(x) } else { x };");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    #[should_panic(expected = "type char doesn't match type int")]
+    fn test_narrowing_else_branch() {
+        type_of("@is__character: (x: Any) -> x is char;
+            let len <- fn(s: char): int { 3 };
+            fn(x: int | char): int { if (is__character(x)) { x } else { len(x) } };");
+    }
+
+    #[test]
+    fn test_narrowing_type_guard() {
+        let typ = type_of("@is_int: (x: Any) -> x is int;
+            let y: int | bool <- 3;
+            let n: int <- 3;
+            if (is_int(y) and true) { y } else { n };");
+        assert_eq!(typ.pretty(), "int");
+        // a failed (a and b) doesn't tell which one failed
+        let typ = type_of("@is_int: (x: Any) -> x is int;
+            let y: int | bool <- 3;
+            let twice <- fn(n: int): int { n };
+            if (is_int(y) and true) { twice(y) } else { y };");
+        assert!(matches!(typ, Type::Union(types, _) if types.len() == 2));
+    }
+
+    #[test]
+    fn test_narrowing_tag_test() {
+        let typ = type_of("type Opt = .Some(int) | .None;
+            @is_some: (x: Any) -> x is .Some(Any);
+            let o: Opt <- None;
+            let n: int <- 3;
+            if (is_some(o)) { o } else { Some(n) };");
+        assert_eq!(typ.pretty(), ".Some(int)");
+        let typ = type_of("type Opt = .Some(int) | .None;
+            @is_some: (x: Any) -> x is .Some(Any);
+            let o: Opt <- None;
+            if (is_some(o)) { None } else { o };");
+        assert_eq!(typ.pretty(), ".None");
+    }
+
+    #[test]
+    fn test_nullable_propagates_through_arithmetic() {
+        let typ = type_of("@add: (T, T) -> T;
+            let x: int? <- NA;
+            add(x, 1);");
+        assert_eq!(typ.pretty(), "int?");
+    }
+
+    #[test]
+    fn test_nullable_comparison() {
+        let typ = type_of("let x: num? <- NA;
+            x > 2.5;");
+        assert_eq!(typ.pretty(), "bool?");
+    }
+
+    #[test]
+    #[should_panic(expected = "can be NA")]
+    fn test_nullable_argument() {
+        type_of("let twice <- fn(n: int): int { n };
+            let x: int? <- NA;
+            twice(x);");
+    }
+
+    // nchar(NA) is 2 in R, so nchar doesn't propagate NA
+    #[test]
+    #[should_panic(expected = "can be NA")]
+    fn test_nchar_of_nullable() {
+        type_of("@nchar: (a: char) -> int;
+            let s: char? <- NA;
+            nchar(s);");
+    }
+
+    #[test]
+    fn test_nullable_narrowing() {
+        let typ = type_of("@is__na: (x: Any) -> x is Empty;
+            let twice <- fn(n: int): int { n };
+            let x: int? <- NA;
+            if (is__na(x)) { 0 } else { twice(x) };");
+        assert!(matches!(typ, Type::Integer(_, _)));
+    }
+
+    // is.na is vectorised: it doesn't narrow a vector, it tests each element
+    #[test]
+    fn test_na_of_vector() {
+        let typ = type_of(&format!("{}\nlet xs <- [1, 2];\nis__na(xs);", include_str!("../configs/r/std.ty")));
+        assert_eq!(typ.pretty(), "[2, bool]");
+    }
+
+    // NA isn't NULL: is.null doesn't remove the NA of an int?
+    #[test]
+    #[should_panic(expected = "type int doesn't match type")]
+    fn test_null_is_not_na() {
+        type_of(&format!("{}\nlet twice <- fn(n: int): int {{ n }};
+            let x: int? <- 3;
+            if (is__null(x)) {{ 0 }} else {{ twice(x) }};", include_str!("../configs/r/std.ty")));
+    }
+
+    #[test]
+    fn test_null_narrowing() {
+        let typ = type_of(&format!("{}\nlet twice <- fn(n: int): int {{ n }};
+            let v: Class(\"NULL\") | int <- 3;
+            if (is__null(v)) {{ 0 }} else {{ twice(v) }};", include_str!("../configs/r/std.ty")));
+        assert!(matches!(typ, Type::Integer(_, _)));
+    }
+
+    #[test]
+    fn test_data_frame_constructor() {
+        let typ = type_of("data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});");
+        assert_eq!(typ.pretty(), "DataFrame<{id: int, score: num}>");
+    }
+
+    #[test]
+    fn test_data_frame_column() {
+        let typ = type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            df$score;");
+        assert!(matches!(typ, Type::Array(_, ref elem, _) if matches!(**elem, Type::Number(_))));
+    }
+
+    #[test]
+    fn test_data_frame_row_binding() {
+        let typ = type_of("let df <- data__frame(:{id: [1, 2, 3]});
+            let both <- rbind(df, df);
+            both$id;");
+        match typ {
+            Type::Array(rows, _, _) => assert_eq!(*rows, Type::Integer(Tint::Val(6), HelpData::default())),
+            _ => panic!("expected an array, got {}", typ.pretty())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "record of arrays")]
+    fn test_data_frame_unequal_columns() {
+        type_of("data__frame(:{id: [1, 2, 3], score: [1.5, 2.5]});");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't exist")]
+    fn test_data_frame_unknown_column() {
+        type_of("let df <- data__frame(:{id: [1, 2, 3]});
+            df$name;");
+    }
+
+    #[test]
+    fn test_data_frame_from_csv() {
+        let typ = type_of("@read__csv: (file: char) -> DataFrame<Any>;
+            let df: DataFrame<{id: int}> <- read__csv(\"a.csv\");
+            df$id;");
+        assert!(matches!(typ, Type::Array(_, ref elem, _) if matches!(**elem, Type::Integer(_, _))));
+    }
+
+    #[test]
+    fn test_select_projects_columns() {
+        let typ = type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            select(df, score);");
+        assert_eq!(typ.pretty(), "DataFrame<{score: num}>");
+    }
+
+    #[test]
+    fn test_mutate_extends_columns() {
+        let typ = type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            mutate(df, passed = (score > 2.0));");
+        assert_eq!(typ.pretty(), "DataFrame<{id: int, score: num, passed: bool}>");
+    }
+
+    #[test]
+    fn test_summarise_columns() {
+        let typ = type_of("@mean: (a: [#N, T]) -> T;
+            let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            let summary <- summarise(df, avg = mean(score));
+            summary$avg;");
+        match typ {
+            Type::Array(rows, _, _) => assert_eq!(*rows, Type::Integer(Tint::Val(1), HelpData::default())),
+            _ => panic!("expected an array, got {}", typ.pretty())
+        }
+    }
+
+    #[test]
+    fn test_select_on_record() {
+        let typ = type_of("let point <- :{x: 1, y: 2.5};
+            select(point, y);");
+        assert_eq!(typ.pretty(), "{y: num}");
+    }
+
+    #[test]
+    fn test_select_shadowed_by_user() {
+        let typ = type_of("let select <- fn(p: {x: int, y: int}, n: int): int { p$x };
+            let point <- :{x: 1, y: 2};
+            select(point, 3);");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    #[should_panic(expected = "The field 'scor' doesn't exist")]
+    fn test_select_unknown_column() {
+        type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            select(df, scor);");
+    }
+
+    #[test]
+    fn test_select_several_columns() {
+        let typ = type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0], name: [\"a\", \"b\", \"c\"]});
+            select(df, id, score);");
+        assert_eq!(typ.pretty(), "DataFrame<{id: int, score: num}>");
+    }
+
+    // the filter of std.R keeps the elements of a vector that satisfy a predicate
+    #[test]
+    fn test_vector_filter_with_std() {
+        let typ = type_of(&format!("{}\nlet xs <- [1, 2, 3];\nfilter(xs, fn(x: int): bool {{ true }});",
+            include_str!("../configs/r/std.ty")));
+        assert!(matches!(typ, Type::Array(_, ref elem, _) if matches!(**elem, Type::Integer(_, _))));
+    }
+
+    #[test]
+    fn test_column_through_std() {
+        let std = include_str!("../configs/r/std.ty");
+        let data = "let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});";
+        assert_eq!(type_of(&format!("{}\n{}\nmean(df$score);", std, data)).pretty(), "num");
+        assert_eq!(type_of(&format!("{}\n{}\nmap(df$score, fn(s: num): bool {{ true }});", std, data)).pretty(), "[3, bool]");
+        assert_eq!(type_of(&format!("{}\n{}\nfilter(df, score > 2.0);", std, data)).pretty(), "DataFrame<{id: int, score: num}>");
+    }
+
+    #[test]
+    #[should_panic(expected = "The variable scor")]
+    fn test_filter_unknown_column() {
+        type_of("let df <- data__frame(:{id: [1, 2, 3], score: [1.5, 2.5, 3.0]});
+            filter(df, scor > 2.0);");
+    }
+
+    #[test]
+    fn test_factor_from_level() {
+        let typ = type_of("let size: Factor<\"low\" | \"mid\" | \"high\"> <- \"mid\";
+            size;");
+        assert_eq!(typ.pretty(), "Factor<\"low\" | \"mid\" | \"high\">");
+    }
+
+    #[test]
+    #[should_panic(expected = "'medium' isn't a level")]
+    fn test_factor_unknown_level() {
+        type_of("let size: Factor<\"low\" | \"high\"> <- \"medium\";
+            size;");
+    }
+
+    #[test]
+    #[should_panic(expected = "'hgh' isn't a level")]
+    fn test_factor_array_unknown_level() {
+        type_of("let sizes: [2, Factor<\"low\" | \"high\">] <- [\"low\", \"hgh\"];
+            sizes;");
+    }
+
+    #[test]
+    fn test_factor_table() {
+        let typ = type_of("let sizes: [3, Factor<\"low\" | \"high\">] <- [\"low\", \"high\", \"low\"];
+            table(sizes);");
+        assert_eq!(typ.pretty(), "{low: int, high: int}");
+    }
+
+    #[test]
+    fn test_factor_levels() {
+        let typ = type_of("let size: Factor<\"low\" | \"mid\" | \"high\"> <- \"mid\";
+            levels(size);");
+        match typ {
+            Type::Array(len, _, _) => assert_eq!(*len, Type::Integer(Tint::Val(3), HelpData::default())),
+            _ => panic!("expected an array, got {}", typ.pretty())
+        }
+    }
+
+    #[test]
+    fn test_factor_exhaustive_match() {
+        let typ = type_of("let size: Factor<\"low\" | \"high\"> <- \"low\";
+            match size as s {
+                \"low\" => 1,
+                \"high\" => 2,
+            };");
+        assert!(matches!(typ, Type::Integer(_, _)));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing `Factor<\"high\">`")]
+    fn test_factor_non_exhaustive_match() {
+        type_of("let size: Factor<\"low\" | \"high\"> <- \"low\";
+            match size as s {
+                \"low\" => 1,
+            };");
+    }
+
+    #[test]
+    fn test_matrix_product_shape() {
+        let typ = type_of("let a: [2, [3, num]] <- [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+            a %*% t(a);");
+        assert_eq!(typ.pretty(), "[2, [2, num]]");
+    }
+
+    #[test]
+    fn test_dot_shape() {
+        let typ = type_of("let a: [2, [3, int]] <- [[1, 2, 3], [4, 5, 6]];
+            dot(a, t(a));");
+        assert_eq!(typ.pretty(), "[2, [2, int]]");
+    }
+
+    #[test]
+    fn test_matrix_binding_shape() {
+        let typ = type_of("let a: [2, [3, num]] <- [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+            rbind(cbind(a, a), cbind(a, a));");
+        assert_eq!(typ.pretty(), "[4, [6, num]]");
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched dimensions")]
+    fn test_matrix_product_mismatch() {
+        type_of("let a: [2, [3, num]] <- [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+            a %*% a;");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match type")]
+    fn test_matrix_element_mismatch() {
+        type_of("let a: [2, [2, num]] <- [[1.0, 2.0], [3.0, 4.0]];
+            let b: [2, [2, char]] <- [[\"a\", \"b\"], [\"c\", \"d\"]];
+            cbind(a, b);");
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched dimensions")]
+    fn test_solve_needs_square_matrix() {
+        type_of("let a: [2, [3, num]] <- [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+            solve(a);");
+    }
+
+    #[test]
+    fn test_index_solved_from_length() {
+        let typ = type_of("@tail: (a: [#N+1, T]) -> [#N, T];
+            tail([1, 2, 3, 4, 5]);");
+        assert_eq!(typ.pretty(), "[4, int(1)]");
+    }
+
+    #[test]
+    fn test_index_solved_with_coefficient() {
+        let typ = type_of("@halves: (a: [2*#N, T]) -> [#N, T];
+            halves([1, 2, 3, 4, 5, 6]);");
+        assert_eq!(typ.pretty(), "[3, int(1)]");
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't solve the index")]
+    fn test_unsolvable_index() {
+        type_of("@split: (a: [#N*#M, T]) -> [#N, T];
+            split([1, 2, 3, 4, 5]);");
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't solve the index")]
+    fn test_index_solved_negative() {
+        type_of("@tail: (a: [#N+6, T]) -> [#N, T];
+            tail([1, 2, 3, 4, 5]);");
+    }
+
+    #[test]
+    fn test_index_simplified() {
+        let typ = type_of("@same: (a: [#N, T]) -> [#N+1-1, T];
+            same([1, 2, 3]);");
+        assert_eq!(typ.pretty(), "[3, int(1)]");
+    }
+
+    #[test]
+    fn test_dependent_length() {
+        let typ = type_of("@first: (a: [#N, num] if #N > 0) -> num;
+            first([1.0, 2.0]);");
+        assert_eq!(typ.pretty(), "num");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't satisfy #N > 2")]
+    fn test_dependent_length_rejected() {
+        type_of("@third: (a: [#N, num] if #N > 2) -> num;
+            third([1.0, 2.0]);");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't satisfy $L in")]
+    fn test_dependent_label_rejected() {
+        type_of("@pick: (a: $L if $L in (\"a\", \"b\")) -> char;
+            pick(\"c\");");
+    }
+
+    #[test]
+    fn test_row_field_added() {
+        let typ = type_of("@with_id: (r: {*$L: *T}) -> {*$L: *T, id: int};
+            with_id(:{name: \"a\", age: true});");
+        assert_eq!(typ.pretty(), "{name: char, age: bool, id: int}");
+    }
+
+    #[test]
+    fn test_row_field_replaced() {
+        let typ = type_of("@rename: (r: {*$L: *T, age: int}) -> {*$L: *T, years: int};
+            rename(:{name: \"a\", age: 3});");
+        assert_eq!(typ.pretty(), "{name: char, years: int}");
+    }
+
+    #[test]
+    fn test_row_kept_by_body() {
+        let typ = type_of("let keep <- fn(r: {*$L: *T, age: int}): {*$L: *T, age: int} { r };
+            keep(:{name: \"a\", age: 3});");
+        assert_eq!(typ.pretty(), "{name: char, age: int}");
+    }
+
+    // the body doesn't know the fields of $L, so it can't build them
+    #[test]
+    #[should_panic(expected = "don't match it's type")]
+    fn test_row_rigid_in_body() {
+        type_of("let rename <- fn(r: {*$L: *T, age: int}): {*$L: *T, years: int} { :{years: 1} };
+            rename(:{name: \"a\", age: 3});");
+    }
+
+    #[test]
+    fn test_row_subtype() {
+        let pattern = ltype("{*$L: *T, age: int}".into()).unwrap().1;
+        let context = Context::default();
+        assert!(type_of(":{name: \"a\", age: 3};").is_subtype(&pattern, &context));
+        assert!(!type_of(":{name: \"a\"};").is_subtype(&pattern, &context));
+    }
+
+    #[test]
+    fn test_tuple_row() {
+        let typ = type_of("@wrap: (t: {*T}) -> {bool, *T};
+            wrap(:{1, \"a\"});");
+        assert_eq!(typ.pretty(), "{bool, int(1), char}");
+    }
+
+    #[test]
+    #[should_panic(expected = "is immutable")]
+    fn test_assign_immutable() {
+        type_of("let x <- 1;
+            x <- 2;");
+    }
+
+    #[test]
+    fn test_assign_mutable() {
+        let typ = type_of("let mut x <- 1;
+            x <- 2;
+            x");
+        assert_eq!(typ.pretty(), "int(2)");
+    }
+
+    #[test]
+    #[should_panic(expected = "is immutable")]
+    fn test_assign_field_of_immutable_record() {
+        type_of("let p <- :{a: 1, b: 2};
+            p$a <- 3;");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match type char")]
+    fn test_assign_field_type() {
+        type_of("let mut p <- :{a: 1, b: 2};
+            p$a <- \"x\";");
+    }
+
+    #[test]
+    fn test_dependent_subtype() {
+        let expected = if_type("[#N, num] if #N >= 2".into()).unwrap().1;
+        let context = Context::default();
+        assert!(type_of("[1.0, 2.0];").is_subtype(&expected, &context));
+        assert!(!type_of("[1.0];").is_subtype(&expected, &context));
+    }
+
+    #[test]
+    fn test_opaque_built_in_module() {
+        let typ = type_of_program("module auth {
+                pub opaque User = {name: char};
+                pub let make <- fn(n: char): User { :{name: n} };
+                pub let name <- fn(u: User): char { u$name };
+            };
+            use auth::{make, name};
+            name(make(\"a\"));");
+        assert_eq!(typ.pretty(), "char");
+    }
+
+    #[test]
+    fn test_opaque_module_codegen() {
+        let code = r_of_program("module auth {
+                pub opaque User = {name: char};
+                pub let make <- fn(n: char): User { :{name: n} };
+                pub let name <- fn(u: User): char { u$name };
+            };
+            use auth::{make, name};
+            name(make(\"a\"));");
+        assert!(code.contains("registerS3method('make', 'character', auth$make.character, envir = auth)"));
+        assert!(code.contains("make <- auth$make"));
+        assert!(code.contains("name <- auth$name"));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't build the opaque type")]
+    fn test_opaque_built_outside() {
+        type_of_program("module auth {
+                pub opaque Email = char;
+            };
+            use auth::{Email};
+            let e: Email <- \"x\";");
+    }
+
+    #[test]
+    fn test_opaque_built_at_top_level() {
+        let typ = type_of_program("opaque Email = char;
+            let mk <- fn(s: char): Email { s };
+            mk(\"a\");");
+        assert_eq!(typ.pretty(), "Email");
+    }
+
+    #[test]
+    #[should_panic(expected = "is private to its module")]
+    fn test_opaque_field_outside() {
+        type_of_program("module auth {
+                pub opaque User = {name: char};
+                pub let make <- fn(n: char): User { :{name: n} };
+            };
+            use auth::{make};
+            let u <- make(\"a\");
+            u$name;");
+    }
+
+    #[test]
+    fn test_recursive_alias() {
+        let typ = type_of("type List<T> = .Nil | .Cons({head: T, tail: List<T>});
+            let l: List<int> <- Cons(:{head: 1, tail: Cons(:{head: 2, tail: Nil})});
+            l;");
+        assert_eq!(typ.pretty(), "List<int>");
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match type")]
+    fn test_recursive_alias_rejected() {
+        type_of("type Nat = .Zero | .Succ(Nat);
+            let n: Nat <- Succ(Succ(Three));");
+    }
+
+    #[test]
+    fn test_mutually_recursive_aliases() {
+        let typ = type_of("type Tree<T> = .Node({value: T, children: Forest<T>});
+            type Forest<T> = .Empty | .More({first: Tree<T>, rest: Forest<T>});
+            let t: Tree<int> <- Node(:{value: 1, children: More(:{first: Node(:{value: 2, children: Empty}), rest: Empty})});
+            t;");
+        assert_eq!(typ.pretty(), "Tree<int>");
+    }
+
+    #[test]
+    fn test_recursive_alias_unification() {
+        let typ = type_of("type List<T> = .Nil | .Cons({head: T, tail: List<T>});
+            let first <- fn(xs: List<T>, default: T): T { default };
+            let l: List<int> <- Cons(:{head: 1, tail: Nil});
+            first(l, 0);");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let typ = type_of("type Show = interface { show: fn(a: Self): char };
+            type Point = {x: int, y: int};
+            impl Show for Point { let show <- fn(p: Point): char { \"point\" }; };
+            let p: Point <- :{x: 1, y: 2};
+            show(p);");
+        assert_eq!(typ.pretty(), "char");
+    }
+
+    #[test]
+    #[should_panic(expected = "lacks the method")]
+    fn test_impl_missing_method() {
+        type_of("type Show = interface { show: fn(a: Self): char };
+            impl Show for int { };");
+    }
+
+    #[test]
+    #[should_panic(expected = "instead of")]
+    fn test_impl_wrong_signature() {
+        type_of("type Show = interface { show: fn(a: Self): char };
+            impl Show for int { let show <- fn(i: int): int { i }; };");
+    }
+
+    #[test]
+    #[should_panic(expected = "already implemented")]
+    fn test_conflicting_impl() {
+        type_of("type Show = interface { show: fn(a: Self): char };
+            impl Show for int { let show <- fn(i: int): char { \"a\" }; };
+            impl Show for int { let show <- fn(i: int): char { \"b\" }; };");
+    }
+
+    #[test]
+    fn test_overload_most_specific() {
+        let typ = type_of("@plus: (int, int) -> int;
+            @plus: (num, num) -> num;
+            plus(1.5, 2.5);");
+        assert_eq!(typ.pretty(), "num");
+    }
+
+    #[test]
+    fn test_overload_generic_fallback() {
+        let typ = type_of("@pick: (int, T) -> int;
+            @pick: (T, T) -> char;
+            pick(1, 2);");
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    #[should_panic(expected = "ambiguous")]
+    fn test_ambiguous_call() {
+        type_of("@pick: (int, T) -> int;
+            @pick: (T, int) -> char;
+            pick(1, 2);");
+    }
+
+    #[test]
+    #[should_panic(expected = "No signature of plus")]
+    fn test_no_matching_overload() {
+        type_of("@plus: (int, int) -> int;
+            @plus: (num, num) -> num;
+            plus(1, 2.5);");
+    }
+
+    // the signatures of configs/r/default.ty for these names
+    fn default_signatures(names: &[&str]) -> String {
+        include_str!("../configs/r/default.ty").lines()
+            .filter(|line| names.iter().any(|name| line.starts_with(&format!("@{}:", name))))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // the arithmetic signatures of configs/r/default.ty, declared for int and for num
+    fn default_arithmetic() -> String {
+        default_signatures(&["add", "minus", "mul", "div"])
+    }
+
+    #[test]
+    fn test_default_overloads_int() {
+        let typ = type_of(&format!("{}\n1 + 2;", default_arithmetic()));
+        assert_eq!(typ.pretty(), "int");
+    }
+
+    #[test]
+    fn test_default_overloads_num() {
+        let typ = type_of(&format!("{}\n1.5 + 2.5;", default_arithmetic()));
+        assert_eq!(typ.pretty(), "num");
+    }
 }
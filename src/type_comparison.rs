@@ -5,6 +5,11 @@ use crate::var::Var;
 use crate::context::Context;
 use crate::tag::Tag;
 use crate::help_data::HelpData;
+use crate::tchar::Tchar;
+use crate::index_solver::is_index_expression;
+use crate::row::has_row;
+use crate::row::row_subtype;
+use crate::row::tuple_row;
 
 pub fn is_subset(v1: &[(Var, Type)], v2: &[(Var, Type)], cont: &Context) -> bool {
     v1.iter().all(|(v1, t1)| {
@@ -40,19 +45,19 @@ fn contains_all(cont: &Context, vec1: &[ArgumentType], vec2: &[ArgumentType]) ->
         })
 }
 
-pub fn contains_all2(vec1: &[ArgumentType], vec2: &[ArgumentType]) -> bool {
+pub fn contains_all2(cont: &Context, vec1: &[ArgumentType], vec2: &[ArgumentType]) -> bool {
     vec1.iter()
         .any(|sub| {
             vec2.iter()
                 .any(|sup| 
                      (sub.get_argument() == sup.get_argument())
-                     && sub.get_type().is_subtype(&sup.get_type(), &Context::default()))
+                     && sub.get_type().is_subtype(&sup.get_type(), cont))
         })
 }
 
 fn to_self(t1: Type, t2: Type) -> Type {
     if  t1 == t2 {
-        Type::Alias("Self".to_string(), vec![], "".into(), true, t1.into())
+        Type::Alias("Self".to_string(), vec![], "".into(), true, t1.get_help_data())
     } else {
        t1
     }
@@ -102,10 +107,29 @@ pub fn is_subtype(context: &Context, type1: &Type, type2: &Type) -> bool {
         (Type::Array(n1, t1, _), Type::Array(n2, t2, _)) => {
             is_subtype(context, n1, n2) && is_subtype(context, t1, t2)
         },
+        // a recursive type compares by its parameters instead of unfolding forever
+        (Type::Alias(name1, params1, path1, _, _), Type::Alias(name2, params2, path2, _, _))
+            if name1 == name2 && path1 == path2 => {
+            params1.len() == params2.len() &&
+            params1.iter().zip(params2.iter()).all(|(t1, t2)| is_subtype(context, t1, t2))
+        },
+        (Type::Alias(_, _, _, _, _), _) => {
+            let reduced = reduce_type(context, type1);
+            reduced != *type1 && is_subtype(context, &reduced, type2)
+        },
         (type1, Type::Alias(_, _, _, _, _)) => {
+            // an opaque type stays itself
             let reduced = reduce_type(context, type2);
-            is_subtype(context, type1, &reduced)
+            reduced != *type2 && is_subtype(context, type1, &reduced)
         },
+        (Type::Nullable(t1, _), Type::Nullable(t2, _)) => is_subtype(context, t1, t2),
+        (Type::Empty(_), Type::Nullable(_, _)) => true,
+        (type1, Type::Nullable(t2, _)) => is_subtype(context, type1, t2),
+        (Type::DataFrame(rows1, schema1, _), Type::DataFrame(rows2, schema2, _))
+            => schema1.is_any() || (is_subtype(context, rows1, rows2) && is_subtype(context, schema1, schema2)),
+        (Type::Char(Tchar::Val(level), _), Type::Factor(levels, _)) => levels.contains(level),
+        (Type::Factor(levels1, _), Type::Factor(levels2, _))
+            => levels1.iter().all(|level| levels2.contains(level)),
         (Type::Function(_, args1, ret_typ1, _), Type::Function(_, args2, ret_typ2, _)) => {
             args1.iter().chain([&(**ret_typ1)])
                 .zip(args2.iter().chain([&(**ret_typ2)]))
@@ -128,7 +152,13 @@ pub fn is_subtype(context: &Context, type1: &Type, type2: &Type) -> bool {
             )
         }
 
+        (Type::Tuple(_, _), Type::Tuple(types, _)) if tuple_row(types).is_some() => true,
+        (Type::Formula(vars1, _), Type::Formula(vars2, _))
+            => vars2.is_empty() || vars1.iter().all(|var| vars2.contains(var)),
+
         // Record subtyping
+        (Type::Record(r1, _), Type::Record(r2, _)) if has_row(r2)
+            => row_subtype(r1, r2, |typ1, typ2| is_subtype(context, typ1, typ2)),
         (Type::Record(r1, _), Type::Record(r2, _)) => {
             if has_generic_label(r2) && (r1.len() == r2.len()) {
                 all_subtype(context, r1, r2)
@@ -156,6 +186,8 @@ pub fn is_subtype(context: &Context, type1: &Type, type2: &Type) -> bool {
         (Type::Integer(_, _), Type::IndexGen(_, _)) => true,
         (Type::Char(_, _), Type::LabelGen(_, _)) => true,
         (Type::IndexGen(_, _), Type::IndexGen(_, _)) => true,
+        (Type::Integer(_, _) | Type::IndexGen(_, _), expr @ (Type::Add(_, _, _) | Type::Minus(_, _, _) | Type::Mul(_, _, _) | Type::Div(_, _, _)))
+            => is_index_expression(expr),
 
         // Params subtyping
         (Type::Params(p1, _), Type::Params(p2, _)) => {
@@ -178,6 +210,8 @@ pub fn is_matching(context: &Context, type1: &Type, type2: &Type) -> bool {
     match (type1, type2) {
         (Type::Empty(_), _) | (_, Type::Empty(_)) => true,
         (Type::Any(_), _) | (_, Type::Any(_)) => true,
+        (Type::Alias(name1, _, path1, _, _), Type::Alias(name2, _, path2, _, _))
+            if name1 == name2 && path1 == path2 => is_subtype(context, type1, type2),
         
         // Reduce types and check again
         _ => {
@@ -192,29 +226,39 @@ pub fn is_matching(context: &Context, type1: &Type, type2: &Type) -> bool {
 
 pub fn reduce_param(
     context: &Context,
-    param: &ArgumentType  // List of pairs [X, Y1]
+    param: &ArgumentType,  // List of pairs [X, Y1]
+    expanding: &[String]
 ) -> ArgumentType {     // Returns list of pairs [X, Y2]
     
     // Reduce the type part of each parameter
-    let reduced_type = reduce_type(context, &param.get_type());
+    let reduced_type = reduce_folding(context, &param.get_type(), expanding);
     ArgumentType(param.get_argument(), reduced_type, param.2.to_owned())
 }
 
 pub fn reduce_type(context: &Context, type_: &Type) -> Type {
+    reduce_folding(context, type_, &[])
+}
+
+// A recursive alias (type List<T> = .Nil | .Cons({head: T, tail: List<T>}))
+// is unfolded once: its occurrences inside its own expansion stay folded
+// and are unfolded again when a comparison reaches them.
+fn reduce_folding(context: &Context, type_: &Type, expanding: &[String]) -> Type {
     match type_ {
         Type::Record(args, h) => {
             Type::Record(args.iter()
-                .map(|arg| reduce_param(context, arg))
+                .map(|arg| reduce_param(context, arg, expanding))
                 .collect(), h.clone())
         },
-        Type::Alias(_name, concret_types, path, opacity, _h) => {
-            if *opacity == true {
-                type_.clone()
-            } else {
-                let var = Var::from_type(type_.clone())
-                    .expect(&format!("The alias {} is malformed", type_))
-                    .set_path(path.clone());
-                if let Some((aliased_type, generics)) = context.get_matching_alias_signature(&var) {
+        Type::Alias(name, _, path, _, _) if expanding.contains(&format!("{}{}", path, name))
+            => type_.clone(),
+        Type::Alias(name, concret_types, path, _opacity, _h) => {
+            let var = Var::from_type(type_.clone())
+                .expect(&format!("The alias {} is malformed", type_))
+                .set_path(path.clone());
+            // the context decides: an opaque type is open inside its module
+            match context.get_matching_alias_signature(&var) {
+                Some((opaque @ Type::Alias(_, _, _, true, _), _)) => opaque,
+                Some((aliased_type, generics)) => {
                     let substituted = type_substitution(
                         &aliased_type,
                         &generics.iter()
@@ -222,8 +266,12 @@ pub fn reduce_type(context: &Context, type_: &Type) -> Type {
                             .map(|(gen, typ)| (gen.clone(), typ.clone()))
                             .collect::<Vec<_>>()
                     );
-                    reduce_type(context, &substituted)
-                } else {
+                    let expanding = expanding.iter().cloned()
+                        .chain([format!("{}{}", path, name)])
+                        .collect::<Vec<_>>();
+                    reduce_folding(context, &substituted, &expanding)
+                },
+                None => {
                     let mvar = Var::from_type(type_.clone()).unwrap();
                     if mvar.is_opaque() {
                         type_.clone() 
@@ -237,15 +285,23 @@ pub fn reduce_type(context: &Context, type_: &Type) -> Type {
 
         Type::StrictUnion(types, h) => {
             Type::StrictUnion(types.iter()
-                .map(|t| reduce_type(context, &t.to_type()))
+                .map(|t| reduce_folding(context, &t.to_type(), expanding))
                 .flat_map(Tag::from_type)
                 .collect(), h.clone())
         }
 
         Type::Tag(name, inner, h) => {
-            Type::Tag(name.clone(), Box::new(reduce_type(context, inner)), h.clone())
+            Type::Tag(name.clone(), Box::new(reduce_folding(context, inner, expanding)), h.clone())
         }
+        Type::Nullable(typ, h) => Type::Nullable(Box::new(reduce_folding(context, typ, expanding)), h.clone()),
+        Type::DataFrame(rows, schema, h)
+            => Type::DataFrame(rows.clone(), Box::new(reduce_folding(context, schema, expanding)), h.clone()),
         Type::If(typ, _conditions, _) => *typ.clone(),
+        Type::Function(kinds, args, ret, h) => {
+            Type::Function(kinds.clone(),
+                args.iter().map(|arg| reduce_folding(context, arg, expanding)).collect(),
+                Box::new(reduce_folding(context, ret, expanding)), h.clone())
+        },
         _ => type_.clone()
     }
 }
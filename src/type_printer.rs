@@ -1,15 +1,18 @@
 use crate::Type;
-use crate::kind::Kind;
 use crate::help_data::HelpData;
 use crate::tint::Tint;
 use crate::tchar::Tchar;
 use crate::type_category::TypeCategory;
 
-fn format_kind(ki: &Kind) -> String {
-   match ki {
-       Kind::Type => "k_type",
-       _ => "k_index"
-   }.to_string()
+// the values of a condition: #N > 0, $L in ("a", "b")
+fn format_operand(ty: &Type) -> String {
+    match ty {
+        Type::Tuple(values, _) => format!("({})", values.iter().map(format_operand).collect::<Vec<_>>().join(", ")),
+        Type::Integer(Tint::Val(i), _) => i.to_string(),
+        Type::Char(Tchar::Val(c), _) => format!("\"{}\"", c),
+        Type::LabelGen(l, _) => format!("${}", l),
+        typ => format(typ)
+    }
 }
 
 pub fn format(ty: &Type) -> String {
@@ -26,7 +29,7 @@ pub fn format(ty: &Type) -> String {
             format!("[{}, {}]", format2(dim), format(ty))
         },
         Type::Function(kinds, params, ret_ty, _h) => {
-            let formatted_kinds = kinds.iter().map(|arg_kind| format_kind(&arg_kind.get_kind())).collect::<Vec<_>>();
+            let formatted_kinds = kinds.iter().map(|arg_kind| format(&arg_kind.get_argument())).collect::<Vec<_>>();
             let formatted_params = params.iter().map(|param| format(param)).collect::<Vec<_>>();
             format!("fn<{}>({}) -> {}", formatted_kinds.join(", "), formatted_params.join(", "), format(ret_ty))
         }
@@ -58,7 +61,7 @@ pub fn format(ty: &Type) -> String {
         }
         Type::Any(_) => "any".to_string(),
         Type::IndexGen(i, _) => format!("#{}", i),
-        Type::LabelGen(l, _) => format!("%{}", l),
+        Type::LabelGen(l, _) => format!("${}", l),
         Type::Tuple(elements, _) => {
             let body = elements.iter()
                 .map(format)
@@ -71,7 +74,17 @@ pub fn format(ty: &Type) -> String {
         Type::Mul(a, b, _) => format!("{}*{}", a, b),
         Type::Div(a, b, _) => format!("{}/{}", a, b),
         Type::RFunction(_) => "RFunction".to_string(),
+        Type::Formula(_, _) => "Formula".to_string(),
+        Type::Guard(name, typ, _) => format!("{} is {}", name, typ.pretty()),
+        Type::Nullable(typ, _) => format!("{}?", typ.pretty()),
+        Type::DataFrame(_, schema, _) => format!("DataFrame<{}>", schema.pretty()),
+        Type::Factor(levels, _) => format!("Factor<{}>", levels.iter().map(|level| format!("\"{}\"", level)).collect::<Vec<_>>().join(" | ")),
         Type::RClass(elem, _) => format!("class({})", elem.iter().cloned().collect::<Vec<_>>().join(", ")),
+        Type::If(typ, conditions, _) => format!("{} if {}", typ.pretty(), conditions.iter().map(format).collect::<Vec<_>>().join(" and ")),
+        Type::Condition(lhs, ope, rhs, _) => format!("{} {} {}", format_operand(lhs), format(ope), format_operand(rhs)),
+        Type::In(_) => "in".to_string(),
+        Type::Comparison(ope, _) => ope.clone(),
+        Type::Multi(typ, _) => format!("*{}", format(typ)),
         Type::Union(s, _) => format!("{}", s.iter().cloned().map(|x| x.pretty()).collect::<Vec<_>>().join(" | ")),
         t => format!("{:?}", t)
     }
@@ -94,6 +107,7 @@ pub fn format2(t: &Type) -> String {
             }
         },
         Type::IndexGen(idgen, _) => format!("#{}", idgen),
+        Type::Multi(typ, _) => format!("*{}", format(typ)),
         val if val.to_category() == TypeCategory::Template
             => val.pretty(),
         val => panic!("{:?} doesn't have a second format", val)
@@ -1,7 +1,9 @@
 use nom::IResult;
 use nom::character::complete::multispace0;
+use nom::character::complete::multispace1;
 use nom::bytes::complete::tag;
 use nom::combinator::opt;
+use nom::combinator::not;
 use nom::character::complete::alpha1;
 use crate::argument_type::ArgumentType;
 use nom::character::complete::one_of;
@@ -34,7 +36,7 @@ use crate::elements;
 type Span<'a> = LocatedSpan<&'a str, String>;
 
 fn ltype_arg(s: Span) -> IResult<Span, Type> {
-    let res = (ltype, terminated(opt(tag(",")), multispace0)).parse(s);
+    let res = (alt((if_type, ltype)), terminated(opt(tag(",")), multispace0)).parse(s);
     match res {
         Ok((s, (t, _))) => Ok((s, t)),
         Err(r) => Err(r)
@@ -139,8 +141,17 @@ fn simple_label(s: Span) -> IResult<Span, Type> {
     }
 }
 
+// *$L: the labels of a row variable
+fn row_label(s: Span) -> IResult<Span, Type> {
+    let res = preceded(tag("*"), label_generic).parse(s);
+    match res {
+        Ok((s, typ)) => Ok((s, Type::Multi(Box::new(typ.clone()), typ.get_help_data()))),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn label(s: Span) -> IResult<Span, Type> {
-    alt((label_generic, simple_label)).parse(s)
+    alt((row_label, label_generic, simple_label)).parse(s)
 }
 
 pub fn argument(s: Span) -> IResult<Span, ArgumentType> {
@@ -183,6 +194,59 @@ fn record_type(s: Span) -> IResult<Span, Type> {
     }
 }
 
+// DataFrame<{id: int, name: char}>, DataFrame<Any> when the columns aren't known
+fn data_frame_type(s: Span) -> IResult<Span, Type> {
+    let res = (
+            terminated(tag("DataFrame"), multispace0),
+            terminated(tag("<"), multispace0),
+            alt((record_type, terminated(any, multispace0))),
+            terminated(tag(">"), multispace0)
+                    ).parse(s);
+    match res {
+        Ok((s, (start, _, schema, _))) => {
+            let rows = Type::Integer(Tint::Unknown, start.clone().into());
+            Ok((s, Type::DataFrame(Box::new(rows), Box::new(schema), start.into())))
+        },
+        Err(r) => Err(r)
+    }
+}
+
+// "low" as the type of this only value
+fn char_literal(s: Span) -> IResult<Span, Type> {
+    let res = elements::chars(s);
+    match res {
+        Ok((s, Lang::Char(c, h))) => Ok((s, Type::Char(Tchar::Val(c), h))),
+        Ok(_) => unreachable!(),
+        Err(r) => Err(r)
+    }
+}
+
+fn level(s: Span) -> IResult<Span, String> {
+    let res = elements::chars(s);
+    match res {
+        Ok((s, Lang::Char(c, _))) => Ok((s, c)),
+        Ok(_) => unreachable!(),
+        Err(r) => Err(r)
+    }
+}
+
+fn factor_type(s: Span) -> IResult<Span, Type> {
+    let res = (
+            terminated(tag("Factor"), multispace0),
+            terminated(tag("<"), multispace0),
+            level,
+            many0(preceded(terminated(tag("|"), multispace0), level)),
+            terminated(tag(">"), multispace0)
+                    ).parse(s);
+    match res {
+        Ok((s, (start, _, first, others, _))) => {
+            let levels = [first].into_iter().chain(others).collect();
+            Ok((s, Type::Factor(levels, start.into())))
+        },
+        Err(r) => Err(r)
+    }
+}
+
 fn number(s: Span) -> IResult<Span, Type> {
     let res = (tag("num"), multispace0).parse(s);
     match res {
@@ -218,6 +282,30 @@ fn type_params(s: Span) -> IResult<Span, Vec<Type>> {
 }
 
 
+// T or T: Addable
+fn bounded_generic(s: Span) -> IResult<Span, (Type, Option<Type>)> {
+    let res = (
+        generic,
+        opt(preceded(terminated(tag(":"), multispace0), ltype)),
+        opt(terminated(tag(","), multispace0))).parse(s);
+    match res {
+        Ok((s, (gen, bound, _))) => Ok((s, (gen, bound))),
+        Err(r) => Err(r)
+    }
+}
+
+// the generics declared by a function: fn<T: Addable, U>(...)
+pub fn generic_bounds(s: Span) -> IResult<Span, Vec<(Type, Option<Type>)>> {
+    let res = (
+        terminated(tag("<"), multispace0),
+        many0(bounded_generic),
+        terminated(tag(">"), multispace0)).parse(s);
+    match res {
+        Ok((s, (_, v, _))) => Ok((s, v)),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn pascal_case(s: Span) -> IResult<Span, (String, HelpData)> {
     let res = terminated((one_of("ABCDEFGHIJKLMNOPQRSTUVWXYZ"), alphanumeric1), multispace0).parse(s);
     match res {
@@ -341,8 +429,9 @@ fn strict_union(s: Span) -> IResult<Span, Type> {
    }
 }
 
+// the keywords of a dependent type (T if #N > 0 and $L in ...) aren't members of the union
 fn union_helper(s: Span) -> IResult<Span, Type> {
-    terminated(terminated(utype, opt(tag("|"))), multispace0).parse(s)
+    terminated(terminated(preceded(not(alt((tag("if "), tag("in "), tag("and ")))), utype), opt(tag("|"))), multispace0).parse(s)
 }
 
 fn union(s: Span) -> IResult<Span, Type> {
@@ -480,6 +569,25 @@ fn any(s: Span) -> IResult<Span, Type> {
     }
 }
 
+fn formula(s: Span) -> IResult<Span, Type> {
+    match tag("Formula")(s) {
+        Ok((s, e)) => Ok((s, Type::Formula(vec![], e.into()))),
+        Err(r) => Err(r)
+    }
+}
+
+// return type of a type guard: x is int
+pub fn guard_type(s: Span) -> IResult<Span, Type> {
+    let res = (
+        terminated(variable_exp, multispace1),
+        terminated(tag("is"), multispace1),
+        ltype).parse(s);
+    match res {
+        Ok((s, ((name, h), _, typ))) => Ok((s, Type::Guard(name, Box::new(typ), h))),
+        Err(r) => Err(r)
+    }
+}
+
 fn empty(s: Span) -> IResult<Span, Type> {
     match tag("Empty")(s) {
         Ok((s, e)) => Ok((s, Type::Empty(e.into()))),
@@ -519,7 +627,10 @@ fn index_operator(s: Span) -> IResult<Span, (Type, Op)> {
                 index
                 ).parse(s);
     match res {
-        Ok((s, (Some(ope), ele))) => Ok((s, (ele, ope))),
+        Ok((s, (Some(ope), ele))) => match ope {
+            Op::Add(_) | Op::Minus(_) | Op::Mul(_) | Op::Div(_) => Ok((s, (ele, ope))),
+            _ => Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Tag)))
+        },
         Ok((s, (None, ele))) => Ok((s.clone(), (ele, Op::Empty(s.into())))),
         Err(r) => Err(r)
     }
@@ -567,17 +678,33 @@ fn multitype(s: Span) -> IResult<Span, Type> {
     }
 }
 
+// #N in (1, 2, 3)
+fn condition_values(s: Span) -> IResult<Span, Type> {
+    let res = (
+                terminated(tag("("), multispace0),
+                many1(ltype_parameter),
+                terminated(tag(")"), multispace0)).parse(s);
+    match res {
+        Ok((s, (ope, v, _cl))) => Ok((s, Type::Tuple(v, ope.into()))),
+        Err(r) => Err(r)
+    }
+}
+
 fn type_condition(s: Span) -> IResult<Span, Type> {
-    let res = (ltype, op, ltype).parse(s);
+    let res = (
+                ltype,
+                op,
+                alt((condition_values, ltype)),
+                opt(terminated(tag("and "), multispace0))).parse(s);
     match res {
-        Ok((s, (t1, ope, t2))) => {
+        Ok((s, (t1, ope, t2, _))) => {
             let new_ope = ope.to_type()
                 .expect("This is not a valid type operator");
             Ok((s, Type::Condition(
                         Box::new(t1),
                         Box::new(new_ope.clone()),
                         Box::new(t2), 
-                        new_ope.into())))
+                        new_ope.get_help_data())))
         },
         Err(r) => Err(r)
     }
@@ -588,7 +715,7 @@ pub fn if_type(s: Span) -> IResult<Span,Type> {
     let res = (ltype, tag("if "), many1(type_condition)).parse(s);
     match res {
         Ok((s, (typ, _if, t_conds))) 
-            => Ok((s, Type::If(Box::new(typ.clone()), t_conds, typ.into()))),
+            => Ok((s, Type::If(Box::new(typ.clone()), t_conds, typ.get_help_data()))),
         Err(r) => Err(r)
     }
 }
@@ -606,52 +733,75 @@ fn r_class(s: Span) -> IResult<Span, Type> {
     }
 }
 
+// int? is an int that can be NA
+fn nullable(s: Span, typ: Type) -> IResult<Span, Type> {
+    let res = opt(terminated(tag("?"), multispace0)).parse(s);
+    match res {
+        Ok((s, Some(mark))) => Ok((s, Type::Nullable(Box::new(typ), mark.into()))),
+        Ok((s, None)) => Ok((s, typ)),
+        Err(r) => Err(r)
+    }
+}
+
 pub fn utype(s: Span) -> IResult<Span, Type> {
-    terminated(alt((
+    let res = terminated(alt((
+            tag_default,
             r_class,
             any,
             empty,
+            formula,
             interface,
             label_generic,
             index_algebra,
             number,
             integer,
             boolean,
-            chars,
+            alt((chars, char_literal)),
+            alt((data_frame_type, factor_type)),
             type_alias,
             generic,
             array_type,
             function_type,
             tuple_type,
             record_type,
-            )), multispace0).parse(s)
+            )), multispace0).parse(s);
+    match res {
+        Ok((s, typ)) => nullable(s, typ),
+        Err(r) => Err(r)
+    }
 }
 
 
 //ltype to not use the reserved symbol "type"
 // main
 pub fn ltype(s: Span) -> IResult<Span, Type> {
-    terminated(alt((
+    let res = terminated(alt((
             union,
             multitype,
             r_class,
             any,
             empty,
+            formula,
             interface,
             label_generic,
             index_algebra,
             number,
             integer,
             boolean,
-            chars,
+            alt((chars, char_literal)),
             strict_union,
+            alt((data_frame_type, factor_type)),
             type_alias,
             generic,
             array_type,
             function_type,
             tuple_type,
             record_type,
-            )), multispace0).parse(s)
+            )), multispace0).parse(s);
+    match res {
+        Ok((s, typ)) => nullable(s, typ),
+        Err(r) => Err(r)
+    }
 }
 
 #[cfg(test)]
@@ -659,6 +809,7 @@ mod tests {
     use super::*;
     use crate::builder;
     use crate::Adt;
+    use crate::Context;
 
     #[test]
     fn test_function_type() {
@@ -678,6 +829,45 @@ mod tests {
         assert_eq!(res.to_string(), "tfn([var('number'), var('number')], var('number'))");
     }
 
+    #[test]
+    fn test_nullable_type() {
+        let res = ltype("int?".into()).unwrap().1;
+        assert_eq!(res.pretty(), "int?");
+    }
+
+    #[test]
+    fn test_data_frame_type() {
+        let res = ltype("DataFrame<{id: int, name: char}>".into()).unwrap().1;
+        assert_eq!(res.pretty(), "DataFrame<{id: int, name: char}>");
+    }
+
+    #[test]
+    fn test_factor_type() {
+        let res = ltype("Factor<\"low\" | \"mid\" | \"high\">".into()).unwrap().1;
+        assert_eq!(res.pretty(), "Factor<\"low\" | \"mid\" | \"high\">");
+    }
+
+    #[test]
+    fn test_row_type() {
+        let res = ltype("{*$L: *T, id: int}".into()).unwrap().1;
+        assert_eq!(res.pretty(), "{*$L: *T, id: int}");
+        assert_eq!(ltype("*{$L: T}".into()).unwrap().1, ltype("{*$L: *T}".into()).unwrap().1);
+    }
+
+    #[test]
+    fn test_dependent_type() {
+        let res = if_type("[#N, num] if #N > 0 and #N in (2, 4)".into()).unwrap().1;
+        assert_eq!(res.pretty(), "[#N, num] if #N > 0 and #N in (2, 4)");
+    }
+
+    #[test]
+    fn test_generic_bounds() {
+        let res = generic_bounds("<T: Addable, U>".into()).unwrap().1;
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].1.clone().map(|bound| bound.pretty()), Some("Addable".to_string()));
+        assert_eq!(res[1].1, None);
+    }
+
     #[test]
     fn test_generic() {
         let res = generic("A".into()).unwrap().1;
@@ -899,7 +1089,7 @@ mod tests {
         let arr1 = ltype("[1, T]".into()).unwrap().1;
         let arr2 = ltype("[1, 1]".into()).unwrap().1;
         assert_eq!(
-            arr2.is_subtype(&arr1),
+            arr2.is_subtype(&arr1, &Context::default()),
             true);
     }
 
@@ -3,6 +3,11 @@ use crate::argument_type::ArgumentType;
 use crate::Context;
 use crate::type_comparison;
 use crate::tag::Tag;
+use crate::row::has_row;
+use crate::row::row_bindings;
+use crate::row::expand_row;
+use crate::row::expand_tuple;
+use crate::row::tuple_row;
 
 pub fn type_substitution(type_: &Type, substitutions: &[(Type, Type)]) -> Type {
     if substitutions.is_empty() {
@@ -94,11 +99,17 @@ pub fn type_substitution(type_: &Type, substitutions: &[(Type, Type)]) -> Type {
         Type::Record(fields, h) => {
             Type::Record(
                 fields.iter()
-                    .map(|arg_type| {
-                        ArgumentType(
-                            arg_type.0.clone(),
+                    .flat_map(|arg_type| match (&arg_type.0, &arg_type.1) {
+                        (Type::Multi(label, _), Type::Multi(typ, _)) => {
+                            let (labels, types) = (type_substitution(label, substitutions), type_substitution(typ, substitutions));
+                            expand_row(&labels, &types)
+                                .unwrap_or(vec![ArgumentType(Type::Multi(Box::new(labels.clone()), labels.get_help_data()),
+                                                             Type::Multi(Box::new(types.clone()), types.get_help_data()), arg_type.2)])
+                        },
+                        _ => vec![ArgumentType(
+                            type_substitution(&arg_type.0, substitutions),
                             type_substitution(&arg_type.1, substitutions),
-                            arg_type.2)
+                            arg_type.2)]
                     })
                     .collect(), h.clone()
             )
@@ -146,6 +157,20 @@ pub fn type_substitution(type_: &Type, substitutions: &[(Type, Type)]) -> Type {
             Type::StrictUnion(new_types, h.clone())
         }
 
+        Type::Union(types, h) => {
+            Type::Union(types.iter()
+                .map(|typ| type_substitution(typ, substitutions))
+                .collect(), h.clone())
+        }
+
+        Type::Multi(typ, h) => Type::Multi(Box::new(type_substitution(typ, substitutions)), h.clone()),
+
+        Type::Tuple(types, h) => {
+            Type::Tuple(expand_tuple(&types.iter()
+                .map(|typ| type_substitution(typ, substitutions))
+                .collect::<Vec<_>>()), h.clone())
+        }
+
         // Default case: return the type unchanged
         _ => type_.clone()
     }
@@ -186,6 +211,11 @@ fn unification_helper(values: &[Type], type1: &Type, type2: &Type
             vec![(Type::LabelGen(g.clone(), h2.clone()), Type::Char(s.clone(), h.clone()))]
         }
 
+        // labels of a row variable
+        (Type::Tuple(labels, h), Type::LabelGen(g, h2)) | (Type::LabelGen(g, h2), Type::Tuple(labels, h)) => {
+            vec![(Type::LabelGen(g.clone(), h2.clone()), Type::Tuple(labels.clone(), h.clone()))]
+        }
+
         // Index generic case with number
         (Type::Integer(i, h), Type::IndexGen(g, h2)) | (Type::IndexGen(g, h2), Type::Integer(i, h)) => {
             vec![(Type::IndexGen(g.clone(), h2.clone()), Type::Integer(*i, h.clone()))]
@@ -223,7 +253,19 @@ fn unification_helper(values: &[Type], type1: &Type, type2: &Type
             unification_helper(values, type1, type2)
         }
 
+        // Tuple row case
+        (Type::Tuple(_, _), Type::Tuple(types, _)) if tuple_row(types).is_some() => {
+            unification_helper(values, type1, &tuple_row(types).unwrap())
+        }
+
         // Record case
+        (Type::Record(fields1, _), Type::Record(fields2, _)) if has_row(fields2) => {
+            row_bindings(fields1, fields2).unwrap_or_default().iter()
+                .fold(vec![], |mut matches, (t1, t2)| {
+                    merge_substitutions(&mut matches, unification_helper(values, t1, t2));
+                    matches
+                })
+        }
         (Type::Record(fields1, _), Type::Record(fields2, _)) => {
             if let Some((intersection1, intersection2)) = record_intersection(fields1, fields2) {
                 let types1: Vec<_> = intersection1.iter().map(|arg| &arg.1).collect();
@@ -22,6 +22,9 @@ impl SafeHashMap {
             Some((Type::Generic(_, _), Type::Integer(_, _))) => { 
                self.map.push((key, value.generalize())) 
             },
+            Some((dim @ Type::IndexGen(_, _), va)) => if !(va.exact_match(&value)) {
+                panic!("{}", TypeError::DimensionMismatch(dim.clone(), va.clone(), value).display())
+            },
             Some((_ke, va)) => if !(va.exact_match(&value)) { 
                 None.expect(
                     &TypeError::Param(va.to_owned(), value).display()
@@ -10,7 +10,8 @@ use crate::path::Path;
 use crate::translatable::RTranslatable;
 
 type Name = String;
-type IsMutableOpaque = bool;
+type IsMutable = bool;
+type IsOpaque = bool;
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Eq, Hash)]
 pub enum Permission {
@@ -28,14 +29,15 @@ impl fmt::Display for Permission {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Eq, Hash)]
-pub struct Var(pub Name, pub Path, pub Permission, pub IsMutableOpaque, pub Type, pub HelpData);
+pub struct Var(pub Name, pub Path, pub Permission, pub IsMutable, pub IsOpaque, pub Type, pub HelpData);
 
 // main
 impl Var {
+    // a Lang::Variable names a value, which is never opaque: only type aliases are
     pub fn from_language(l: Lang) -> Option<Var> {
         match l {
             Lang::Variable(name, path, perm, muta, typ, h) 
-                => Some(Var(name, path, perm, muta, typ, h)),
+                => Some(Var(name, path, perm, muta, false, typ, h)),
             _ => None
         }
     }
@@ -64,45 +66,54 @@ impl Var {
             "".into(),
             Permission::Private,
             false,
+            false,
             Type::Empty(HelpData::default()),
             HelpData::default())
     }
 
     pub fn to_language(self) -> Lang {
-        Lang::Variable(self.0, self.1, self.2, self.3, self.4, self.5)
+        Lang::Variable(self.0, self.1, self.2, self.3, self.5, self.6)
     }
 
     pub fn set_name(self, s: &str) -> Var {
-       Var(s.to_string(), self.1, self.2, self.3, self.4, self.5)
+       Var(s.to_string(), self.1, self.2, self.3, self.4, self.5, self.6)
     }
 
     pub fn set_type(self, typ: Type, context: &Context) -> Var {
-        let typ = if let Type::Function(_, params, _, h) = typ.reduce(context) {
+        // an alias unknown to the context (e.g. while parsing) can't hide a function type
+        let unknown_alias = match &typ {
+            Type::Alias(_, _, path, false, _) => Var::from_type(typ.clone())
+                .map(|var| context.get_matching_alias_signature(&var.set_path(path.clone())).is_none())
+                .unwrap_or(false),
+            _ => false
+        };
+        let reduced = if unknown_alias { typ.clone() } else { typ.reduce(context) };
+        let typ = if let Type::Function(_, params, _, h) = reduced {
             if params.len() >= 1 {
                 params[0].clone()
             } else { Type::Any(h) }
         } else { typ };
-        Var(self.0, self.1, self.2, self.3, typ, self.5)
+        Var(self.0, self.1, self.2, self.3, self.4, typ, self.6)
     }
 
     pub fn set_permission(self, perm: bool) -> Var {
         let new_perm = if perm == true { Permission::Public } else { Permission::Private };
-        Var(self.0, self.1, new_perm, self.3, self.4, self.5)
+        Var(self.0, self.1, new_perm, self.3, self.4, self.5, self.6)
     }
 
     pub fn set_mutability(self, muta: bool) -> Var {
-        Var(self.0, self.1, self.2, muta, self.4, self.5)
+        Var(self.0, self.1, self.2, muta, self.4, self.5, self.6)
     }
 
     pub fn set_opacity(self, opa: bool) -> Var {
-        Var(self.0, self.1, self.2, opa, self.4, self.5)
+        Var(self.0, self.1, self.2, self.3, opa, self.5, self.6)
     }
 
     pub fn add_path(self, name: Path) -> Var {
         if self.1 == Path::default() {
-            Var(self.0, name.into(), self.2, self.3, self.4, self.5)
+            Var(self.0, name.into(), self.2, self.3, self.4, self.5, self.6)
         } else {
-            Var(self.0, self.1 + name, self.2, self.3, self.4, self.5)
+            Var(self.0, self.1 + name, self.2, self.3, self.4, self.5, self.6)
         }
     }
 
@@ -119,15 +130,15 @@ impl Var {
     }
 
     pub fn set_path(self, new_path: Path) -> Var {
-        Var(self.0, new_path, self.2, self.3, self.4, self.5)
+        Var(self.0, new_path, self.2, self.3, self.4, self.5, self.6)
     }
 
     pub fn get_type(&self) -> Type {
-        self.4.clone()
+        self.5.clone()
     }
 
     pub fn get_help_data(&self) -> HelpData {
-        self.5.clone()
+        self.6.clone()
     }
 
     pub fn match_with(&self, var: &Var, context: &Context) -> bool {
@@ -137,11 +148,11 @@ impl Var {
     }
 
     pub fn set_help_data(self, h: HelpData) -> Var {
-        Var(self.0, self.1, self.2, self.3, self.4, h)
+        Var(self.0, self.1, self.2, self.3, self.4, self.5, h)
     }
 
     pub fn is_mutable(&self) -> bool {
-        self.is_variable() && self.3.clone()
+        self.is_variable() && self.3
     }
 
     pub fn is_private(&self) -> bool {
@@ -172,11 +183,11 @@ impl Var {
     }
 
     pub fn is_opaque(&self) -> bool {
-        self.is_alias() && self.3
+        self.is_alias() && self.4
     }
 
     pub fn get_opacity(&self) -> bool {
-        self.3
+        self.4
     }
 
     pub fn to_alias(self) -> Type  {
@@ -206,13 +217,13 @@ impl Var {
 impl fmt::Display for Var {
     fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             write!(f, "{}{}<{}>",
-            self.1, self.0, self.4)       
+            self.1, self.0, self.5)       
     }
 }
 
 impl Default for Var {
     fn default() -> Self {
-        Var("".to_string(), "".into(), Permission::Private, false, Type::Empty(HelpData::default()), HelpData::default())
+        Var("".to_string(), "".into(), Permission::Private, false, false, Type::Empty(HelpData::default()), HelpData::default())
     }
 }
 
@@ -228,7 +239,7 @@ impl TryFrom<Lang> for Var {
     fn try_from(value: Lang) -> Result<Self, Self::Error> {
         match value {
             Lang::Variable(name, path, perm, muta, typ, h) 
-                => Ok(Var(name, path, perm, muta, typ, h)),
+                => Ok(Var(name, path, perm, muta, false, typ, h)),
             _ => Err(())
         }
     }
@@ -249,6 +260,7 @@ impl From<&str> for Var {
             "".into(),
             Permission::Private,
             false,
+            false,
             Type::Empty(HelpData::default()),
             HelpData::default())
    } 
@@ -4,6 +4,7 @@ use crate::var::Var;
 use crate::Type;
 use crate::builder;
 use crate::Context;
+use crate::path::Path;
 
 
 #[derive(Debug, Clone, PartialEq)]
@@ -15,7 +16,9 @@ pub struct VarType {
 //main
 impl VarType {
     pub fn new() -> VarType {
-        let var = Var::from("Generic").set_type(builder::params_type(), &Context::default());
+        // built by hand: Var::set_type needs a Context, which itself needs a VarType
+        let Var(name, path, perm, muta, opa, _, h) = Var::from("Generic");
+        let var = Var(name, path, perm, muta, opa, builder::params_type(), h);
         let typ = builder::generic_type();
         VarType {
             variables: vec![],
@@ -99,8 +102,13 @@ impl VarType {
     }
 
     fn push_aliases(self, vt: &[(Var, Type)]) -> Self {
+        // an alias declared ahead (see Context::declare_aliases) isn't repeated
+        let new_aliases = vt.iter()
+            .filter(|alias| !self.aliases.contains(alias))
+            .cloned()
+            .collect::<Vec<_>>();
         VarType {
-            aliases: self.aliases.iter().chain(vt.iter()).cloned().collect(),
+            aliases: self.aliases.iter().chain(new_aliases.iter()).cloned().collect(),
             ..self
         }
     }
@@ -120,14 +128,24 @@ impl VarType {
     }
 
     pub fn get_type_anotation(&self, t: &Type) -> String {
-        let res = match t {
-            Type::Alias(name, _, _, _, _) => name.to_string(),
-            _ => self.aliases.iter()
-                    .find(|(_, typ)| typ == t)
-                    .map(|(var, _)| var.get_name())
-                    .unwrap_or("Generic".to_string())
-        };
-        format!("{}()", res)
+        match t {
+            // x |> factor(levels = c('low', 'high'))
+            Type::Factor(levels, _) => format!("factor(levels = c({}))", levels.iter()
+                    .map(|level| format!("'{}'", level))
+                    .collect::<Vec<_>>().join(", ")),
+            Type::Array(_, elem_ty, _) if matches!(**elem_ty, Type::Factor(_, _))
+                => self.get_type_anotation(elem_ty),
+            _ => {
+                let res = match t {
+                    Type::Alias(name, _, _, _, _) => name.to_string(),
+                    _ => self.aliases.iter()
+                            .find(|(_, typ)| typ == t)
+                            .map(|(var, _)| var.get_name())
+                            .unwrap_or("Generic".to_string())
+                };
+                format!("{}()", res)
+            }
+        }
     }
 
     pub fn get_class_unquoted(&self, t: &Type) -> String {
@@ -170,6 +188,29 @@ impl VarType {
         res
     }
 
+    /// The code of a module names its types without their path and sees
+    /// the structure of its opaque types
+    pub fn open_module(self, path: &str) -> Self {
+        let (own, others): (Vec<_>, Vec<_>) = self.aliases.iter()
+            .cloned()
+            .partition(|(var, _)| var.get_path() == path);
+        let own = own.into_iter()
+            .map(|(var, typ)| (var.set_opacity(false), typ))
+            .collect::<Vec<_>>();
+        let unqualified = own.iter()
+            .filter(|_| !path.is_empty())
+            .map(|(var, typ)| (var.clone().set_path(Path::default()), typ.clone()))
+            .collect::<Vec<_>>();
+        let aliases = others.into_iter()
+            .chain(own)
+            .chain(unqualified)
+            .collect::<Vec<_>>();
+        Self {
+            aliases,
+            ..self
+        }
+    }
+
     pub fn print_aliases(&self) -> String {
         self.aliases.iter()
             .map(|(var, typ)| format!("{} = {}", var.get_name(), typ.pretty()))